@@ -0,0 +1,342 @@
+//! Events and errors appended to the exported Solidity interface.
+//!
+//! `export-abi` only describes public functions. This module prints the
+//! events and custom errors the contract can emit as a second interface, so
+//! generated bindings can decode logs and revert data too. The declarations
+//! are kept by hand. Tests check each one against the signature and indexed
+//! parameters of the Rust type it describes, and check that every variant
+//! of the contract's error type is listed. A new event must still be added
+//! here by hand: nothing detects one that is missing.
+use openzeppelin_stylus::{
+    access::ownable,
+    token::erc20::{self, extensions::capped},
+};
+use stylus_sdk::alloy_sol_types::{SolError, SolEvent, SolType};
+
+use crate::{achievements, erc6909};
+
+/// Name of the printed interface.
+const INTERFACE_NAME: &str = "IRngTokenEvents";
+
+/// Canonical signature, topic types and Solidity declaration of `$event`.
+macro_rules! event {
+    ($event:ty, $declaration:literal $(,)?) => {
+        (
+            <$event as SolEvent>::SIGNATURE,
+            <<$event as SolEvent>::TopicList as SolType>::SOL_NAME,
+            $declaration,
+        )
+    };
+}
+
+/// Canonical signature, topic types and Solidity declaration of every
+/// event.
+const EVENTS: &[(&str, &str, &str)] = &[
+    event!(
+        erc20::Transfer,
+        "event Transfer(address indexed from, address indexed to, uint256 value)",
+    ),
+    event!(
+        erc20::Approval,
+        "event Approval(address indexed owner, address indexed spender, uint256 value)",
+    ),
+    event!(
+        ownable::OwnershipTransferred,
+        "event OwnershipTransferred(address indexed previous_owner, address indexed new_owner)",
+    ),
+    event!(
+        erc6909::Transfer,
+        "event Transfer(address caller, address indexed sender, address indexed receiver, uint256 indexed id, uint256 amount)",
+    ),
+    event!(
+        erc6909::Approval,
+        "event Approval(address indexed owner, address indexed spender, uint256 indexed id, uint256 amount)",
+    ),
+    event!(
+        erc6909::OperatorSet,
+        "event OperatorSet(address indexed owner, address indexed spender, bool approved)",
+    ),
+    event!(
+        achievements::QuestDefined,
+        "event QuestDefined(uint256 indexed questId, uint256 reward)",
+    ),
+    event!(
+        achievements::QuestAttesterUpdated,
+        "event QuestAttesterUpdated(address indexed attester)",
+    ),
+    event!(
+        achievements::QuestCompleted,
+        "event QuestCompleted(uint256 indexed questId, address indexed account)",
+    ),
+    event!(
+        achievements::QuestRewardClaimed,
+        "event QuestRewardClaimed(uint256 indexed questId, address indexed account, uint256 reward)",
+    ),
+    event!(
+        crate::BridgeConfigured,
+        "event BridgeConfigured(address indexed gateway, address indexed l1Token)",
+    ),
+    event!(
+        crate::SoulboundModeUpdated,
+        "event SoulboundModeUpdated(bool enabled)",
+    ),
+    event!(
+        crate::ApprovalDeadlineSet,
+        "event ApprovalDeadlineSet(address indexed owner, address indexed spender, uint256 deadline)",
+    ),
+    event!(
+        crate::ContractURIUpdated,
+        "event ContractURIUpdated()",
+    ),
+    event!(
+        crate::TransferMemo,
+        "event TransferMemo(address indexed from, address indexed to, uint256 value, bytes memo)",
+    ),
+    event!(
+        crate::TokensRecovered,
+        "event TokensRecovered(address indexed to, uint256 amount)",
+    ),
+];
+
+/// Canonical signature and Solidity declaration of every custom error.
+const ERRORS: &[(&str, &str)] = &[
+    (
+        ownable::OwnableUnauthorizedAccount::SIGNATURE,
+        "error OwnableUnauthorizedAccount(address account)",
+    ),
+    (
+        ownable::OwnableInvalidOwner::SIGNATURE,
+        "error OwnableInvalidOwner(address owner)",
+    ),
+    (
+        erc20::ERC20InsufficientBalance::SIGNATURE,
+        "error ERC20InsufficientBalance(address sender, uint256 balance, uint256 needed)",
+    ),
+    (
+        erc20::ERC20InvalidSender::SIGNATURE,
+        "error ERC20InvalidSender(address sender)",
+    ),
+    (
+        erc20::ERC20InvalidReceiver::SIGNATURE,
+        "error ERC20InvalidReceiver(address receiver)",
+    ),
+    (
+        erc20::ERC20InsufficientAllowance::SIGNATURE,
+        "error ERC20InsufficientAllowance(address spender, uint256 allowance, uint256 needed)",
+    ),
+    (
+        erc20::ERC20InvalidSpender::SIGNATURE,
+        "error ERC20InvalidSpender(address spender)",
+    ),
+    (
+        erc20::ERC20InvalidApprover::SIGNATURE,
+        "error ERC20InvalidApprover(address approver)",
+    ),
+    (
+        erc6909::ERC6909InsufficientBalance::SIGNATURE,
+        "error ERC6909InsufficientBalance(address sender, uint256 balance, uint256 needed, uint256 id)",
+    ),
+    (
+        erc6909::ERC6909InsufficientAllowance::SIGNATURE,
+        "error ERC6909InsufficientAllowance(address spender, uint256 allowance, uint256 needed, uint256 id)",
+    ),
+    (
+        erc6909::ERC6909InvalidSender::SIGNATURE,
+        "error ERC6909InvalidSender(address sender)",
+    ),
+    (
+        erc6909::ERC6909InvalidReceiver::SIGNATURE,
+        "error ERC6909InvalidReceiver(address receiver)",
+    ),
+    (
+        erc6909::ERC6909InvalidSpender::SIGNATURE,
+        "error ERC6909InvalidSpender(address spender)",
+    ),
+    (
+        achievements::QuestUndefined::SIGNATURE,
+        "error QuestUndefined(uint256 questId)",
+    ),
+    (
+        achievements::QuestUnauthorizedAttester::SIGNATURE,
+        "error QuestUnauthorizedAttester(address caller)",
+    ),
+    (
+        achievements::QuestNotCompleted::SIGNATURE,
+        "error QuestNotCompleted(uint256 questId, address account)",
+    ),
+    (
+        achievements::QuestAlreadyClaimed::SIGNATURE,
+        "error QuestAlreadyClaimed(uint256 questId, address account)",
+    ),
+    (
+        crate::BridgeUnauthorizedGateway::SIGNATURE,
+        "error BridgeUnauthorizedGateway(address caller)",
+    ),
+    (
+        crate::BridgeAlreadyConfigured::SIGNATURE,
+        "error BridgeAlreadyConfigured(address gateway, address l1Token)",
+    ),
+    (
+        crate::BridgeInvalidConfiguration::SIGNATURE,
+        "error BridgeInvalidConfiguration(address gateway, address l1Token)",
+    ),
+    (crate::TokenSoulbound::SIGNATURE, "error TokenSoulbound()"),
+    (
+        crate::AllowanceExpired::SIGNATURE,
+        "error AllowanceExpired(address spender, uint256 deadline)",
+    ),
+    (
+        crate::ERC20FailedDecreaseAllowance::SIGNATURE,
+        "error ERC20FailedDecreaseAllowance(address spender, uint256 current_allowance, uint256 requested_decrease)",
+    ),
+    (
+        crate::ERC20AllowanceOverflow::SIGNATURE,
+        "error ERC20AllowanceOverflow(address spender, uint256 current_allowance, uint256 requested_increase)",
+    ),
+    (
+        crate::MulticallInvalidCall::SIGNATURE,
+        "error MulticallInvalidCall(uint256 index)",
+    ),
+    (
+        crate::MulticallUnknownSelector::SIGNATURE,
+        "error MulticallUnknownSelector(uint256 index, bytes4 selector)",
+    ),
+    (
+        capped::ERC20ExceededCap::SIGNATURE,
+        "error ERC20ExceededCap(uint256 increased_supply, uint256 cap)",
+    ),
+    (
+        capped::ERC20InvalidCap::SIGNATURE,
+        "error ERC20InvalidCap(uint256 cap)",
+    ),
+];
+
+/// Prints the events and errors interface to stdout.
+pub fn print_events_and_errors() {
+    println!("\ninterface {INTERFACE_NAME} {{");
+    let events = EVENTS.iter().map(|(_, _, declaration)| declaration);
+    let errors = ERRORS.iter().map(|(_, declaration)| declaration);
+    for declaration in events.chain(errors) {
+        println!("    {declaration};");
+    }
+    println!("}}");
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use super::*;
+
+    /// Reduces a Solidity event or error declaration to its canonical
+    /// signature, e.g. `"event Foo(address indexed a)"` to `"Foo(address)"`.
+    fn canonical(declaration: &str) -> String {
+        let declaration = declaration
+            .trim_start_matches("event ")
+            .trim_start_matches("error ");
+        let (name, params) = declaration.split_once('(').unwrap_or((declaration, ""));
+        let types: Vec<&str> = params
+            .trim_end_matches(')')
+            .split(',')
+            .filter_map(|param| param.split_whitespace().next())
+            .collect();
+        format!("{name}({})", types.join(","))
+    }
+
+    /// Returns the types of the indexed parameters of an event declaration
+    /// as its topic list, after the signature hash, e.g.
+    /// `"event Foo(address indexed a, uint256 b)"` to `"(bytes32,address)"`.
+    fn topics(declaration: &str) -> String {
+        let (_, params) = declaration.split_once('(').unwrap_or((declaration, ""));
+        let indexed = params.trim_end_matches(')').split(',').filter_map(|param| {
+            let mut words = param.split_whitespace();
+            let ty = words.next()?;
+            words.any(|word| word == "indexed").then_some(ty)
+        });
+        let topics: Vec<&str> = core::iter::once("bytes32").chain(indexed).collect();
+        format!("({})", topics.join(","))
+    }
+
+    /// Lists the signatures of the given errors, and fails to compile if
+    /// [`crate::Error`] has a variant that is not listed.
+    macro_rules! error_signatures {
+        ($($variant:ident($error:ty)),* $(,)?) => {{
+            #[allow(dead_code)]
+            fn exhaustive(error: &crate::Error) {
+                match error {
+                    $(crate::Error::$variant(_) => {})*
+                }
+            }
+            [$(<$error as SolError>::SIGNATURE),*]
+        }};
+    }
+
+    #[test]
+    fn declarations_match_exported_types() {
+        for (signature, topic_types, declaration) in EVENTS {
+            assert_eq!(canonical(declaration), *signature, "{declaration}");
+            assert_eq!(topics(declaration), *topic_types, "{declaration}");
+        }
+        for (signature, declaration) in ERRORS {
+            assert_eq!(canonical(declaration), *signature, "{declaration}");
+        }
+    }
+
+    #[test]
+    fn every_contract_error_is_declared() {
+        let signatures = error_signatures![
+            UnauthorizedAccount(ownable::OwnableUnauthorizedAccount),
+            InvalidOwner(ownable::OwnableInvalidOwner),
+            InsufficientBalance(erc20::ERC20InsufficientBalance),
+            InvalidSender(erc20::ERC20InvalidSender),
+            InvalidReceiver(erc20::ERC20InvalidReceiver),
+            InsufficientAllowance(erc20::ERC20InsufficientAllowance),
+            InvalidSpender(erc20::ERC20InvalidSpender),
+            InvalidApprover(erc20::ERC20InvalidApprover),
+            Erc6909InsufficientBalance(erc6909::ERC6909InsufficientBalance),
+            Erc6909InsufficientAllowance(erc6909::ERC6909InsufficientAllowance),
+            Erc6909InvalidSender(erc6909::ERC6909InvalidSender),
+            Erc6909InvalidReceiver(erc6909::ERC6909InvalidReceiver),
+            Erc6909InvalidSpender(erc6909::ERC6909InvalidSpender),
+            QuestUndefined(achievements::QuestUndefined),
+            QuestUnauthorizedAttester(achievements::QuestUnauthorizedAttester),
+            QuestNotCompleted(achievements::QuestNotCompleted),
+            QuestAlreadyClaimed(achievements::QuestAlreadyClaimed),
+            UnauthorizedGateway(crate::BridgeUnauthorizedGateway),
+            BridgeAlreadyConfigured(crate::BridgeAlreadyConfigured),
+            InvalidBridgeConfiguration(crate::BridgeInvalidConfiguration),
+            Soulbound(crate::TokenSoulbound),
+            AllowanceExpired(crate::AllowanceExpired),
+            FailedDecreaseAllowance(crate::ERC20FailedDecreaseAllowance),
+            AllowanceOverflow(crate::ERC20AllowanceOverflow),
+            InvalidMulticall(crate::MulticallInvalidCall),
+            UnknownMulticallSelector(crate::MulticallUnknownSelector),
+            ExceededCap(capped::ERC20ExceededCap),
+            InvalidCap(capped::ERC20InvalidCap),
+        ];
+        for signature in signatures {
+            assert!(
+                ERRORS.iter().any(|(declared, _)| *declared == signature),
+                "{signature} is not declared"
+            );
+        }
+    }
+
+    #[test]
+    fn canonical_strips_names_and_indexed() {
+        assert_eq!(
+            canonical("event Foo(address indexed a, uint256 b)"),
+            "Foo(address,uint256)"
+        );
+        assert_eq!(canonical("error Bar()"), "Bar()");
+    }
+
+    #[test]
+    fn topics_lists_indexed_types() {
+        assert_eq!(
+            topics("event Foo(address indexed a, uint256 b, uint256 indexed c)"),
+            "(bytes32,address,uint256)"
+        );
+        assert_eq!(topics("event Bar()"), "(bytes32)");
+    }
+}
@@ -0,0 +1,286 @@
+//! Admin-defined quests that grant a fixed token reward once completed.
+//!
+//! The embedding contract defines quests and their rewards, an attester
+//! marks quests as completed for an account, and the account claims the
+//! reward once per quest. Minting the reward is left to the embedding
+//! contract, which receives the amount from [`Achievements::_claim`].
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    alloy_sol_types::sol,
+    prelude::*,
+    storage::{StorageAddress, StorageBool, StorageMap, StorageU256},
+};
+
+sol! {
+    /// Emitted when the reward of `questId` is set, zero if removed.
+    #[allow(missing_docs)]
+    event QuestDefined(uint256 indexed questId, uint256 reward);
+
+    /// Emitted when the account allowed to attest completions changes.
+    #[allow(missing_docs)]
+    event QuestAttesterUpdated(address indexed attester);
+
+    /// Emitted when `account` is attested to have completed `questId`.
+    #[allow(missing_docs)]
+    event QuestCompleted(uint256 indexed questId, address indexed account);
+
+    /// Emitted when `account` claims the `reward` of `questId`.
+    #[allow(missing_docs)]
+    event QuestRewardClaimed(uint256 indexed questId, address indexed account, uint256 reward);
+}
+
+sol! {
+    /// `questId` has no reward defined.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error QuestUndefined(uint256 questId);
+
+    /// `caller` is not the quest attester.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error QuestUnauthorizedAttester(address caller);
+
+    /// `account` has not completed `questId`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error QuestNotCompleted(uint256 questId, address account);
+
+    /// `account` already claimed the reward of `questId`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error QuestAlreadyClaimed(uint256 questId, address account);
+}
+
+/// An error that occurred in the [`Achievements`] module.
+#[derive(SolidityError, Debug)]
+pub enum Error {
+    /// The quest has no reward defined.
+    Undefined(QuestUndefined),
+    /// The caller is not the quest attester.
+    UnauthorizedAttester(QuestUnauthorizedAttester),
+    /// The account has not completed the quest.
+    NotCompleted(QuestNotCompleted),
+    /// The account already claimed the quest reward.
+    AlreadyClaimed(QuestAlreadyClaimed),
+}
+
+/// State of the [`Achievements`] module.
+#[storage]
+pub struct Achievements {
+    /// Reward of each quest id, zero if the quest is undefined.
+    pub(crate) rewards: StorageMap<U256, StorageU256>,
+    /// Account allowed to mark quests as completed.
+    pub(crate) attester: StorageAddress,
+    /// Maps quest ids to accounts that completed them.
+    pub(crate) completed: StorageMap<U256, StorageMap<Address, StorageBool>>,
+    /// Maps quest ids to accounts that claimed their reward.
+    pub(crate) claimed: StorageMap<U256, StorageMap<Address, StorageBool>>,
+}
+
+impl Achievements {
+    /// Returns the reward of `quest_id`, zero if undefined.
+    pub fn reward_of(&self, quest_id: U256) -> U256 {
+        self.rewards.get(quest_id)
+    }
+
+    /// Returns the account allowed to mark quests as completed.
+    pub fn attester(&self) -> Address {
+        self.attester.get()
+    }
+
+    /// Returns whether `account` completed `quest_id`.
+    pub fn is_completed(&self, quest_id: U256, account: Address) -> bool {
+        self.completed.get(quest_id).get(account)
+    }
+
+    /// Returns whether `account` claimed the reward of `quest_id`.
+    pub fn is_claimed(&self, quest_id: U256, account: Address) -> bool {
+        self.claimed.get(quest_id).get(account)
+    }
+
+    /// Sets the reward of `quest_id`. A zero `reward` removes the quest.
+    /// Access control is up to the embedding contract.
+    pub fn _define(&mut self, quest_id: U256, reward: U256) {
+        self.rewards.setter(quest_id).set(reward);
+        self.vm().log(QuestDefined {
+            questId: quest_id,
+            reward,
+        });
+    }
+
+    /// Sets the account allowed to mark quests as completed. Access control
+    /// is up to the embedding contract.
+    pub fn _set_attester(&mut self, attester: Address) {
+        self.attester.set(attester);
+        self.vm().log(QuestAttesterUpdated { attester });
+    }
+
+    /// Marks `quest_id` as completed by `account`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::UnauthorizedAttester`] - If the caller is not the attester.
+    /// * [`Error::Undefined`] - If `quest_id` has no reward.
+    pub fn _complete(&mut self, quest_id: U256, account: Address) -> Result<(), Error> {
+        let caller = self.vm().msg_sender();
+        if caller != self.attester.get() {
+            return Err(Error::UnauthorizedAttester(QuestUnauthorizedAttester {
+                caller,
+            }));
+        }
+        if self.rewards.get(quest_id).is_zero() {
+            return Err(Error::Undefined(QuestUndefined { questId: quest_id }));
+        }
+
+        self.completed.setter(quest_id).setter(account).set(true);
+        self.vm().log(QuestCompleted {
+            questId: quest_id,
+            account,
+        });
+        Ok(())
+    }
+
+    /// Marks the reward of `quest_id` as claimed by `account` and returns
+    /// the amount to mint.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::NotCompleted`] - If `account` has not completed the quest.
+    /// * [`Error::AlreadyClaimed`] - If `account` already claimed it.
+    /// * [`Error::Undefined`] - If the quest was removed since completion.
+    pub fn _claim(&mut self, quest_id: U256, account: Address) -> Result<U256, Error> {
+        if !self.is_completed(quest_id, account) {
+            return Err(Error::NotCompleted(QuestNotCompleted {
+                questId: quest_id,
+                account,
+            }));
+        }
+        if self.is_claimed(quest_id, account) {
+            return Err(Error::AlreadyClaimed(QuestAlreadyClaimed {
+                questId: quest_id,
+                account,
+            }));
+        }
+        let reward = self.rewards.get(quest_id);
+        if reward.is_zero() {
+            return Err(Error::Undefined(QuestUndefined { questId: quest_id }));
+        }
+
+        self.claimed.setter(quest_id).setter(account).set(true);
+        self.vm().log(QuestRewardClaimed {
+            questId: quest_id,
+            account,
+            reward,
+        });
+        Ok(reward)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stylus_sdk::{
+        alloy_primitives::{address, Address, U256},
+        alloy_sol_types::SolEvent,
+        testing::*,
+    };
+
+    use super::*;
+
+    const ATTESTER: Address = address!("00000000000000000000000000000000000a77e5");
+    const ALICE: Address = address!("00000000000000000000000000000000000a1ce0");
+    const QUEST: U256 = U256::from_limbs([1, 0, 0, 0]);
+    const REWARD: U256 = U256::from_limbs([50, 0, 0, 0]);
+
+    fn quests(vm: &TestVM) -> Achievements {
+        let mut quests = Achievements::from(vm);
+        quests._set_attester(ATTESTER);
+        quests._define(QUEST, REWARD);
+        quests
+    }
+
+    #[test]
+    fn define_and_set_attester() {
+        let vm = TestVM::default();
+        let quests = quests(&vm);
+
+        assert_eq!(quests.attester(), ATTESTER);
+        assert_eq!(quests.reward_of(QUEST), REWARD);
+        assert_eq!(quests.reward_of(QUEST + U256::ONE), U256::ZERO);
+        let logs = vm.get_emitted_logs();
+        let (topics, _) = logs.last().unwrap();
+        assert_eq!(topics[0], QuestDefined::SIGNATURE_HASH);
+    }
+
+    #[test]
+    fn complete_then_claim() {
+        let vm = TestVM::default();
+        let mut quests = quests(&vm);
+        vm.set_sender(ATTESTER);
+
+        quests._complete(QUEST, ALICE).unwrap();
+        assert!(quests.is_completed(QUEST, ALICE));
+        assert!(!quests.is_claimed(QUEST, ALICE));
+
+        assert_eq!(quests._claim(QUEST, ALICE).unwrap(), REWARD);
+        assert!(quests.is_claimed(QUEST, ALICE));
+        let logs = vm.get_emitted_logs();
+        let (topics, _) = logs.last().unwrap();
+        assert_eq!(topics[0], QuestRewardClaimed::SIGNATURE_HASH);
+    }
+
+    #[test]
+    fn complete_requires_attester_and_defined_quest() {
+        let vm = TestVM::default();
+        let mut quests = quests(&vm);
+
+        vm.set_sender(ALICE);
+        let err = quests._complete(QUEST, ALICE).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnauthorizedAttester(QuestUnauthorizedAttester { caller }) if caller == ALICE
+        ));
+
+        vm.set_sender(ATTESTER);
+        let err = quests._complete(QUEST + U256::ONE, ALICE).unwrap_err();
+        assert!(matches!(err, Error::Undefined(_)));
+        assert!(!quests.is_completed(QUEST, ALICE));
+    }
+
+    #[test]
+    fn claim_requires_completion() {
+        let vm = TestVM::default();
+        let mut quests = quests(&vm);
+
+        let err = quests._claim(QUEST, ALICE).unwrap_err();
+        assert!(matches!(err, Error::NotCompleted(_)));
+    }
+
+    #[test]
+    fn claim_only_once() {
+        let vm = TestVM::default();
+        let mut quests = quests(&vm);
+        vm.set_sender(ATTESTER);
+        quests._complete(QUEST, ALICE).unwrap();
+        quests._claim(QUEST, ALICE).unwrap();
+
+        let err = quests._claim(QUEST, ALICE).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::AlreadyClaimed(QuestAlreadyClaimed { questId, account })
+                if questId == QUEST && account == ALICE
+        ));
+    }
+
+    #[test]
+    fn claim_after_removal_fails() {
+        let vm = TestVM::default();
+        let mut quests = quests(&vm);
+        vm.set_sender(ATTESTER);
+        quests._complete(QUEST, ALICE).unwrap();
+
+        quests._define(QUEST, U256::ZERO);
+        let err = quests._claim(QUEST, ALICE).unwrap_err();
+        assert!(matches!(err, Error::Undefined(_)));
+        assert!(!quests.is_claimed(QUEST, ALICE));
+    }
+}
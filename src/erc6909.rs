@@ -0,0 +1,503 @@
+//! Minimal ERC-6909 multi-token ledger.
+//!
+//! Lets a contract issue distinct token ids (tickets, badges, trophies) next
+//! to its ERC-20 balance without deploying another contract. Issuance is left
+//! to the embedding contract through [`Erc6909::_mint`] and
+//! [`Erc6909::_burn`].
+use alloc::vec::Vec;
+
+use openzeppelin_stylus::utils::introspection::erc165::IErc165;
+use stylus_sdk::{
+    alloy_primitives::{aliases::B32, Address, U256},
+    alloy_sol_types::sol,
+    prelude::*,
+    storage::{StorageBool, StorageMap, StorageU256},
+};
+
+/// ERC-165 identifier of the ERC-6909 interface.
+pub const ERC6909_INTERFACE_ID: B32 = B32::new([0x0f, 0x63, 0x2f, 0xb3]);
+
+/// ERC-165 identifier of the ERC-165 interface itself.
+const ERC165_INTERFACE_ID: B32 = B32::new([0x01, 0xff, 0xc9, 0xa7]);
+
+sol! {
+    /// Emitted when `amount` of token `id` moves from `sender` to `receiver`.
+    #[allow(missing_docs)]
+    event Transfer(address caller, address indexed sender, address indexed receiver, uint256 indexed id, uint256 amount);
+
+    /// Emitted when `owner` sets `spender`'s allowance for token `id`.
+    #[allow(missing_docs)]
+    event Approval(address indexed owner, address indexed spender, uint256 indexed id, uint256 amount);
+
+    /// Emitted when `owner` grants or revokes `spender` as an operator.
+    #[allow(missing_docs)]
+    event OperatorSet(address indexed owner, address indexed spender, bool approved);
+}
+
+sol! {
+    /// `sender` holds less than `needed` of token `id`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ERC6909InsufficientBalance(address sender, uint256 balance, uint256 needed, uint256 id);
+
+    /// `spender`'s allowance for token `id` is less than `needed`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ERC6909InsufficientAllowance(address spender, uint256 allowance, uint256 needed, uint256 id);
+
+    /// Tokens cannot be sent from `sender` (e.g. `Address::ZERO`).
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ERC6909InvalidSender(address sender);
+
+    /// Tokens cannot be sent to `receiver` (e.g. `Address::ZERO`).
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ERC6909InvalidReceiver(address receiver);
+
+    /// `spender` cannot be approved (e.g. `Address::ZERO`).
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ERC6909InvalidSpender(address spender);
+}
+
+/// An error that occurred in the [`Erc6909`] ledger.
+#[derive(SolidityError, Debug)]
+pub enum Error {
+    /// `sender` holds less than `needed` of token `id`.
+    InsufficientBalance(ERC6909InsufficientBalance),
+    /// `spender`'s allowance for token `id` is less than `needed`.
+    InsufficientAllowance(ERC6909InsufficientAllowance),
+    /// Tokens cannot be sent from `sender`.
+    InvalidSender(ERC6909InvalidSender),
+    /// Tokens cannot be sent to `receiver`.
+    InvalidReceiver(ERC6909InvalidReceiver),
+    /// `spender` cannot be approved.
+    InvalidSpender(ERC6909InvalidSpender),
+}
+
+/// State of an [`Erc6909`] ledger.
+#[storage]
+pub struct Erc6909 {
+    /// Maps owners to token ids to balances.
+    pub(crate) balances: StorageMap<Address, StorageMap<U256, StorageU256>>,
+    /// Maps owners to spenders to token ids to allowances.
+    pub(crate) allowances: StorageMap<Address, StorageMap<Address, StorageMap<U256, StorageU256>>>,
+    /// Maps owners to spenders to operator approvals.
+    pub(crate) operators: StorageMap<Address, StorageMap<Address, StorageBool>>,
+}
+
+/// Required interface of an [`Erc6909`] compliant contract.
+pub trait IErc6909 {
+    /// The error type associated to this trait implementation.
+    type Error: Into<Vec<u8>>;
+
+    /// Returns the amount of token `id` owned by `owner`.
+    fn balance_of(&self, owner: Address, id: U256) -> U256;
+
+    /// Returns the amount of token `id` `spender` may move for `owner`.
+    fn allowance(&self, owner: Address, spender: Address, id: U256) -> U256;
+
+    /// Returns whether `spender` is an operator of `owner`.
+    fn is_operator(&self, owner: Address, spender: Address) -> bool;
+
+    /// Moves `amount` of token `id` from the caller to `receiver`.
+    fn transfer(&mut self, receiver: Address, id: U256, amount: U256) -> Result<bool, Self::Error>;
+
+    /// Moves `amount` of token `id` from `sender` to `receiver` using the
+    /// caller's operator status or allowance.
+    fn transfer_from(
+        &mut self,
+        sender: Address,
+        receiver: Address,
+        id: U256,
+        amount: U256,
+    ) -> Result<bool, Self::Error>;
+
+    /// Sets `spender`'s allowance over the caller's token `id` to `amount`.
+    fn approve(&mut self, spender: Address, id: U256, amount: U256) -> Result<bool, Self::Error>;
+
+    /// Grants or revokes `spender` as an operator of all the caller's tokens.
+    fn set_operator(&mut self, spender: Address, approved: bool) -> Result<bool, Self::Error>;
+}
+
+impl IErc6909 for Erc6909 {
+    type Error = Error;
+
+    fn balance_of(&self, owner: Address, id: U256) -> U256 {
+        self.balances.get(owner).get(id)
+    }
+
+    fn allowance(&self, owner: Address, spender: Address, id: U256) -> U256 {
+        self.allowances.get(owner).get(spender).get(id)
+    }
+
+    fn is_operator(&self, owner: Address, spender: Address) -> bool {
+        self.operators.get(owner).get(spender)
+    }
+
+    fn transfer(&mut self, receiver: Address, id: U256, amount: U256) -> Result<bool, Self::Error> {
+        let sender = self.vm().msg_sender();
+        self._transfer(sender, receiver, id, amount)?;
+        Ok(true)
+    }
+
+    fn transfer_from(
+        &mut self,
+        sender: Address,
+        receiver: Address,
+        id: U256,
+        amount: U256,
+    ) -> Result<bool, Self::Error> {
+        let caller = self.vm().msg_sender();
+        if caller != sender && !self.is_operator(sender, caller) {
+            self._spend_allowance(sender, caller, id, amount)?;
+        }
+        self._transfer(sender, receiver, id, amount)?;
+        Ok(true)
+    }
+
+    fn approve(&mut self, spender: Address, id: U256, amount: U256) -> Result<bool, Self::Error> {
+        if spender.is_zero() {
+            return Err(Error::InvalidSpender(ERC6909InvalidSpender { spender }));
+        }
+        let owner = self.vm().msg_sender();
+        self.allowances
+            .setter(owner)
+            .setter(spender)
+            .setter(id)
+            .set(amount);
+        self.vm().log(Approval {
+            owner,
+            spender,
+            id,
+            amount,
+        });
+        Ok(true)
+    }
+
+    fn set_operator(&mut self, spender: Address, approved: bool) -> Result<bool, Self::Error> {
+        if spender.is_zero() {
+            return Err(Error::InvalidSpender(ERC6909InvalidSpender { spender }));
+        }
+        let owner = self.vm().msg_sender();
+        self.operators.setter(owner).setter(spender).set(approved);
+        self.vm().log(OperatorSet {
+            owner,
+            spender,
+            approved,
+        });
+        Ok(true)
+    }
+}
+
+impl IErc165 for Erc6909 {
+    fn supports_interface(&self, interface_id: B32) -> bool {
+        interface_id == ERC6909_INTERFACE_ID || interface_id == ERC165_INTERFACE_ID
+    }
+}
+
+impl Erc6909 {
+    /// Creates `amount` of token `id` and assigns it to `to`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidReceiver`] - If `to` is `Address::ZERO`.
+    pub fn _mint(&mut self, to: Address, id: U256, amount: U256) -> Result<(), Error> {
+        if to.is_zero() {
+            return Err(Error::InvalidReceiver(ERC6909InvalidReceiver {
+                receiver: to,
+            }));
+        }
+        self._update(Address::ZERO, to, id, amount)
+    }
+
+    /// Destroys `amount` of token `id` held by `from`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidSender`] - If `from` is `Address::ZERO`.
+    /// * [`Error::InsufficientBalance`] - If `from` holds less than `amount`.
+    pub fn _burn(&mut self, from: Address, id: U256, amount: U256) -> Result<(), Error> {
+        if from.is_zero() {
+            return Err(Error::InvalidSender(ERC6909InvalidSender { sender: from }));
+        }
+        self._update(from, Address::ZERO, id, amount)
+    }
+
+    fn _transfer(
+        &mut self,
+        sender: Address,
+        receiver: Address,
+        id: U256,
+        amount: U256,
+    ) -> Result<(), Error> {
+        if sender.is_zero() {
+            return Err(Error::InvalidSender(ERC6909InvalidSender { sender }));
+        }
+        if receiver.is_zero() {
+            return Err(Error::InvalidReceiver(ERC6909InvalidReceiver { receiver }));
+        }
+        self._update(sender, receiver, id, amount)
+    }
+
+    /// Moves `amount` of token `id` from `from` to `to`, minting when `from`
+    /// is `Address::ZERO` and burning when `to` is `Address::ZERO`.
+    fn _update(&mut self, from: Address, to: Address, id: U256, amount: U256) -> Result<(), Error> {
+        if !from.is_zero() {
+            let balance = self.balances.get(from).get(id);
+            if balance < amount {
+                return Err(Error::InsufficientBalance(ERC6909InsufficientBalance {
+                    sender: from,
+                    balance,
+                    needed: amount,
+                    id,
+                }));
+            }
+            self.balances.setter(from).setter(id).set(balance - amount);
+        }
+        if !to.is_zero() {
+            let balance = self.balances.get(to).get(id);
+            let balance = balance
+                .checked_add(amount)
+                .expect("should not exceed `U256::MAX` for `balances`");
+            self.balances.setter(to).setter(id).set(balance);
+        }
+
+        let caller = self.vm().msg_sender();
+        self.vm().log(Transfer {
+            caller,
+            sender: from,
+            receiver: to,
+            id,
+            amount,
+        });
+        Ok(())
+    }
+
+    fn _spend_allowance(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        id: U256,
+        amount: U256,
+    ) -> Result<(), Error> {
+        let allowance = self.allowance(owner, spender, id);
+        if allowance == U256::MAX {
+            return Ok(());
+        }
+        if allowance < amount {
+            return Err(Error::InsufficientAllowance(ERC6909InsufficientAllowance {
+                spender,
+                allowance,
+                needed: amount,
+                id,
+            }));
+        }
+        self.allowances
+            .setter(owner)
+            .setter(spender)
+            .setter(id)
+            .set(allowance - amount);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use stylus_sdk::{
+        alloy_primitives::{address, Address, B256, U256},
+        alloy_sol_types::{SolEvent, SolValue},
+        testing::*,
+    };
+
+    use super::*;
+
+    const ALICE: Address = address!("00000000000000000000000000000000000a1ce0");
+    const BOB: Address = address!("0000000000000000000000000000000000000b0b");
+    const CAROL: Address = address!("00000000000000000000000000000000000ca201");
+    const ID: U256 = U256::from_limbs([7, 0, 0, 0]);
+
+    fn ledger(vm: &TestVM) -> Erc6909 {
+        let mut ledger = Erc6909::from(vm);
+        ledger._mint(ALICE, ID, U256::from(100)).unwrap();
+        ledger
+    }
+
+    #[test]
+    fn mint_burn_and_transfer() {
+        let vm = TestVM::default();
+        let mut ledger = ledger(&vm);
+        vm.set_sender(ALICE);
+
+        assert!(ledger.transfer(BOB, ID, U256::from(30)).unwrap());
+        ledger._burn(BOB, ID, U256::from(10)).unwrap();
+
+        assert_eq!(ledger.balance_of(ALICE, ID), U256::from(70));
+        assert_eq!(ledger.balance_of(BOB, ID), U256::from(20));
+        assert_eq!(ledger.balance_of(ALICE, ID + U256::ONE), U256::ZERO);
+    }
+
+    #[test]
+    fn transfer_emits_event() {
+        let vm = TestVM::default();
+        let mut ledger = ledger(&vm);
+        vm.set_sender(ALICE);
+
+        ledger.transfer(BOB, ID, U256::from(30)).unwrap();
+
+        let logs = vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(
+            *topics,
+            vec![
+                Transfer::SIGNATURE_HASH,
+                ALICE.into_word(),
+                BOB.into_word(),
+                B256::from(ID),
+            ]
+        );
+        assert_eq!(*data, (ALICE, U256::from(30)).abi_encode());
+    }
+
+    #[test]
+    fn transfer_rejects_insufficient_balance() {
+        let vm = TestVM::default();
+        let mut ledger = ledger(&vm);
+        vm.set_sender(ALICE);
+
+        let err = ledger.transfer(BOB, ID, U256::from(101)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InsufficientBalance(ERC6909InsufficientBalance { sender, balance, needed, id })
+                if sender == ALICE && balance == U256::from(100) && needed == U256::from(101) && id == ID
+        ));
+        assert_eq!(ledger.balance_of(ALICE, ID), U256::from(100));
+    }
+
+    #[test]
+    fn zero_addresses_are_rejected() {
+        let vm = TestVM::default();
+        let mut ledger = ledger(&vm);
+        vm.set_sender(ALICE);
+
+        let err = ledger.transfer(Address::ZERO, ID, U256::ONE).unwrap_err();
+        assert!(matches!(err, Error::InvalidReceiver(_)));
+        let err = ledger._mint(Address::ZERO, ID, U256::ONE).unwrap_err();
+        assert!(matches!(err, Error::InvalidReceiver(_)));
+        let err = ledger._burn(Address::ZERO, ID, U256::ONE).unwrap_err();
+        assert!(matches!(err, Error::InvalidSender(_)));
+        let err = ledger
+            .transfer_from(Address::ZERO, BOB, ID, U256::ZERO)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidSender(_)));
+        let err = ledger.approve(Address::ZERO, ID, U256::ONE).unwrap_err();
+        assert!(matches!(err, Error::InvalidSpender(_)));
+        let err = ledger.set_operator(Address::ZERO, true).unwrap_err();
+        assert!(matches!(err, Error::InvalidSpender(_)));
+    }
+
+    #[test]
+    fn transfer_from_spends_allowance() {
+        let vm = TestVM::default();
+        let mut ledger = ledger(&vm);
+        vm.set_sender(ALICE);
+        assert!(ledger.approve(BOB, ID, U256::from(5)).unwrap());
+
+        let logs = vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(topics[0], Approval::SIGNATURE_HASH);
+        assert_eq!(*data, U256::from(5).abi_encode());
+
+        vm.set_sender(BOB);
+        ledger
+            .transfer_from(ALICE, CAROL, ID, U256::from(3))
+            .unwrap();
+        assert_eq!(ledger.allowance(ALICE, BOB, ID), U256::from(2));
+        assert_eq!(ledger.balance_of(CAROL, ID), U256::from(3));
+
+        let err = ledger
+            .transfer_from(ALICE, CAROL, ID, U256::from(3))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InsufficientAllowance(ERC6909InsufficientAllowance { spender, allowance, needed, id })
+                if spender == BOB && allowance == U256::from(2) && needed == U256::from(3) && id == ID
+        ));
+    }
+
+    #[test]
+    fn max_allowance_is_not_spent() {
+        let vm = TestVM::default();
+        let mut ledger = ledger(&vm);
+        vm.set_sender(ALICE);
+        ledger.approve(BOB, ID, U256::MAX).unwrap();
+
+        vm.set_sender(BOB);
+        ledger
+            .transfer_from(ALICE, CAROL, ID, U256::from(40))
+            .unwrap();
+        assert_eq!(ledger.allowance(ALICE, BOB, ID), U256::MAX);
+    }
+
+    #[test]
+    fn operator_spends_without_allowance() {
+        let vm = TestVM::default();
+        let mut ledger = ledger(&vm);
+        vm.set_sender(ALICE);
+        ledger.approve(BOB, ID, U256::from(5)).unwrap();
+        assert!(ledger.set_operator(BOB, true).unwrap());
+        assert!(ledger.is_operator(ALICE, BOB));
+
+        let logs = vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(
+            *topics,
+            vec![
+                OperatorSet::SIGNATURE_HASH,
+                ALICE.into_word(),
+                BOB.into_word()
+            ]
+        );
+        assert_eq!(*data, true.abi_encode());
+
+        // Operator status takes precedence, so the allowance is left alone.
+        vm.set_sender(BOB);
+        ledger
+            .transfer_from(ALICE, CAROL, ID, U256::from(50))
+            .unwrap();
+        assert_eq!(ledger.allowance(ALICE, BOB, ID), U256::from(5));
+
+        vm.set_sender(ALICE);
+        ledger.set_operator(BOB, false).unwrap();
+        vm.set_sender(BOB);
+        let err = ledger
+            .transfer_from(ALICE, CAROL, ID, U256::from(6))
+            .unwrap_err();
+        assert!(matches!(err, Error::InsufficientAllowance(_)));
+    }
+
+    #[test]
+    fn owner_transfer_from_needs_no_allowance() {
+        let vm = TestVM::default();
+        let mut ledger = ledger(&vm);
+        vm.set_sender(ALICE);
+
+        ledger
+            .transfer_from(ALICE, BOB, ID, U256::from(10))
+            .unwrap();
+        assert_eq!(ledger.balance_of(BOB, ID), U256::from(10));
+    }
+
+    #[test]
+    fn supports_interface() {
+        let vm = TestVM::default();
+        let ledger = Erc6909::from(&vm);
+
+        assert!(ledger.supports_interface(B32::new([0x0f, 0x63, 0x2f, 0xb3])));
+        assert!(ledger.supports_interface(B32::new([0x01, 0xff, 0xc9, 0xa7])));
+        assert!(!ledger.supports_interface(B32::new([0xff, 0xff, 0xff, 0xff])));
+    }
+}
@@ -1,21 +1,109 @@
 #![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
 extern crate alloc;
 
+#[cfg(any(test, feature = "export-abi"))]
+pub mod abi;
+pub mod achievements;
+pub mod erc6909;
+pub mod rand_utils;
+pub mod request;
+pub mod supra_vrf;
+pub mod wad;
+pub mod weighted;
+
+use achievements::Achievements;
 use alloc::vec::Vec;
+use erc6909::{Erc6909, IErc6909};
 use openzeppelin_stylus::{
     access::ownable::{self, IOwnable, Ownable},
     token::erc20::{
         self,
-        extensions::{Erc20Metadata, IErc20Metadata},
+        extensions::{capped, Capped, Erc20Metadata, ICapped, IErc20Metadata},
         Erc20, IErc20,
     },
     utils::introspection::erc165::IErc165,
 };
 use stylus_sdk::{
+    abi::{Bytes, Router},
     alloy_primitives::{aliases::B32, Address, U256, U8},
+    alloy_sol_types::sol,
     prelude::*,
+    storage::{StorageAddress, StorageBool, StorageMap, StorageString, StorageU256},
 };
 
+sol! {
+    /// Emitted when the owner points the token at an Arbitrum bridge gateway.
+    #[allow(missing_docs)]
+    event BridgeConfigured(address indexed gateway, address indexed l1Token);
+
+    /// Emitted when the owner turns soulbound mode on or off.
+    #[allow(missing_docs)]
+    event SoulboundModeUpdated(bool enabled);
+
+    /// Emitted when `owner`'s approval of `spender` is given an expiry.
+    #[allow(missing_docs)]
+    event ApprovalDeadlineSet(address indexed owner, address indexed spender, uint256 deadline);
+
+    /// Emitted when the contract-level metadata URI changes (ERC-7572).
+    #[allow(missing_docs)]
+    event ContractURIUpdated();
+
+    /// Emitted next to `Transfer` when `value` is sent with a `memo`.
+    #[allow(missing_docs)]
+    event TransferMemo(address indexed from, address indexed to, uint256 value, bytes memo);
+
+    /// Emitted when the owner sweeps `amount` held by the contract to `to`.
+    #[allow(missing_docs)]
+    event TokensRecovered(address indexed to, uint256 amount);
+}
+
+sol! {
+    /// The caller is not the configured L2 token gateway.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error BridgeUnauthorizedGateway(address caller);
+
+    /// The bridge was already configured and cannot be re-pointed.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error BridgeAlreadyConfigured(address gateway, address l1Token);
+
+    /// `gateway` must be a deployed contract and `l1Token` non-zero.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error BridgeInvalidConfiguration(address gateway, address l1Token);
+
+    /// Transfers are disabled while the token is in soulbound mode.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error TokenSoulbound();
+
+    /// `spender`'s allowance expired at `deadline`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error AllowanceExpired(address spender, uint256 deadline);
+
+    /// `spender`'s allowance is lower than the `requested_decrease`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ERC20FailedDecreaseAllowance(address spender, uint256 current_allowance, uint256 requested_decrease);
+
+    /// Increasing `spender`'s allowance by `requested_increase` would exceed `U256::MAX`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error ERC20AllowanceOverflow(address spender, uint256 current_allowance, uint256 requested_increase);
+
+    /// Call `index` of a multicall is too short to hold a selector.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error MulticallInvalidCall(uint256 index);
+
+    /// Call `index` of a multicall selects no function of this contract.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error MulticallUnknownSelector(uint256 index, bytes4 selector);
+}
+
 #[derive(SolidityError, Debug)]
 enum Error {
     // Ownable Errors
@@ -28,6 +116,32 @@ enum Error {
     InsufficientAllowance(erc20::ERC20InsufficientAllowance),
     InvalidSpender(erc20::ERC20InvalidSpender),
     InvalidApprover(erc20::ERC20InvalidApprover),
+    // ERC6909 Errors
+    Erc6909InsufficientBalance(erc6909::ERC6909InsufficientBalance),
+    Erc6909InsufficientAllowance(erc6909::ERC6909InsufficientAllowance),
+    Erc6909InvalidSender(erc6909::ERC6909InvalidSender),
+    Erc6909InvalidReceiver(erc6909::ERC6909InvalidReceiver),
+    Erc6909InvalidSpender(erc6909::ERC6909InvalidSpender),
+    // Quest Errors
+    QuestUndefined(achievements::QuestUndefined),
+    QuestUnauthorizedAttester(achievements::QuestUnauthorizedAttester),
+    QuestNotCompleted(achievements::QuestNotCompleted),
+    QuestAlreadyClaimed(achievements::QuestAlreadyClaimed),
+    // Bridge Errors
+    UnauthorizedGateway(BridgeUnauthorizedGateway),
+    BridgeAlreadyConfigured(BridgeAlreadyConfigured),
+    InvalidBridgeConfiguration(BridgeInvalidConfiguration),
+    // Token Errors
+    Soulbound(TokenSoulbound),
+    AllowanceExpired(AllowanceExpired),
+    FailedDecreaseAllowance(ERC20FailedDecreaseAllowance),
+    AllowanceOverflow(ERC20AllowanceOverflow),
+    // Multicall Errors
+    InvalidMulticall(MulticallInvalidCall),
+    UnknownMulticallSelector(MulticallUnknownSelector),
+    // Capped Errors
+    ExceededCap(capped::ERC20ExceededCap),
+    InvalidCap(capped::ERC20InvalidCap),
 }
 
 impl From<ownable::Error> for Error {
@@ -54,6 +168,38 @@ impl From<erc20::Error> for Error {
     }
 }
 
+impl From<capped::Error> for Error {
+    fn from(value: capped::Error) -> Self {
+        match value {
+            capped::Error::ExceededCap(e) => Error::ExceededCap(e),
+            capped::Error::InvalidCap(e) => Error::InvalidCap(e),
+        }
+    }
+}
+
+impl From<erc6909::Error> for Error {
+    fn from(value: erc6909::Error) -> Self {
+        match value {
+            erc6909::Error::InsufficientBalance(e) => Error::Erc6909InsufficientBalance(e),
+            erc6909::Error::InsufficientAllowance(e) => Error::Erc6909InsufficientAllowance(e),
+            erc6909::Error::InvalidSender(e) => Error::Erc6909InvalidSender(e),
+            erc6909::Error::InvalidReceiver(e) => Error::Erc6909InvalidReceiver(e),
+            erc6909::Error::InvalidSpender(e) => Error::Erc6909InvalidSpender(e),
+        }
+    }
+}
+
+impl From<achievements::Error> for Error {
+    fn from(value: achievements::Error) -> Self {
+        match value {
+            achievements::Error::Undefined(e) => Error::QuestUndefined(e),
+            achievements::Error::UnauthorizedAttester(e) => Error::QuestUnauthorizedAttester(e),
+            achievements::Error::NotCompleted(e) => Error::QuestNotCompleted(e),
+            achievements::Error::AlreadyClaimed(e) => Error::QuestAlreadyClaimed(e),
+        }
+    }
+}
+
 //*//////////////////////////////////////////////////////////////////////////
 //                               LOTTERY TOKEN
 //////////////////////////////////////////////////////////////////////////*//
@@ -64,34 +210,444 @@ struct RngToken {
     erc20: Erc20,
     metadata: Erc20Metadata,
     ownable: Ownable,
+    erc6909: Erc6909,
+    l2_gateway: StorageAddress,
+    l1_address: StorageAddress,
+    soulbound: StorageBool,
+    allowance_deadlines: StorageMap<Address, StorageMap<Address, StorageU256>>,
+    contract_uri: StorageString,
+    achievements: Achievements,
+    capped: Capped,
+    issued: StorageU256,
 }
 
 #[public]
-#[implements(IErc20<Error = Error>, IErc20Metadata, IErc165, IOwnable<Error = Error>)]
+#[implements(
+    IErc20<Error = Error>,
+    IErc20Metadata,
+    ICapped,
+    IErc165,
+    IOwnable<Error = Error>,
+    IErc6909<Error = Error>,
+    IArbToken<Error = Error>
+)]
 impl RngToken {
     #[constructor]
-    pub fn constructor(&mut self, initial_owner: Address) -> Result<(), Error> {
-        self._init(initial_owner)
+    pub fn constructor(
+        &mut self,
+        initial_owner: Address,
+        cap: U256,
+        allocations: Vec<(Address, U256)>,
+    ) -> Result<(), Error> {
+        self._init(initial_owner, cap, allocations)
     }
 
     pub fn mint(&mut self, account: Address, value: U256) -> Result<(), Error> {
         self._mint(account, value)
     }
+
+    pub fn mint_id(&mut self, to: Address, id: U256, amount: U256) -> Result<(), Error> {
+        self._mint_id(to, id, amount)
+    }
+
+    pub fn soulbound(&self) -> bool {
+        self.soulbound.get()
+    }
+
+    pub fn set_soulbound(&mut self, enabled: bool) -> Result<(), Error> {
+        self._set_soulbound(enabled)
+    }
+
+    pub fn transfer_with_memo(
+        &mut self,
+        to: Address,
+        value: U256,
+        memo: Bytes,
+    ) -> Result<bool, Error> {
+        self._transfer_with_memo(to, value, memo)
+    }
+
+    pub fn transfer_batch(&mut self, transfers: Vec<(Address, U256)>) -> Result<bool, Error> {
+        self._transfer_batch(transfers)
+    }
+
+    pub fn transfer_from_batch(
+        &mut self,
+        from: Address,
+        transfers: Vec<(Address, U256)>,
+    ) -> Result<bool, Error> {
+        self._transfer_from_batch(from, transfers)
+    }
+
+    pub fn approve_with_deadline(
+        &mut self,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Result<bool, Error> {
+        self._approve_with_deadline(spender, value, deadline)
+    }
+
+    pub fn increase_allowance(
+        &mut self,
+        spender: Address,
+        added_value: U256,
+    ) -> Result<bool, Error> {
+        self._increase_allowance(spender, added_value)
+    }
+
+    pub fn decrease_allowance(
+        &mut self,
+        spender: Address,
+        subtracted_value: U256,
+    ) -> Result<bool, Error> {
+        self._decrease_allowance(spender, subtracted_value)
+    }
+
+    pub fn allowance_deadline(&self, owner: Address, spender: Address) -> U256 {
+        self.allowance_deadlines.get(owner).get(spender)
+    }
+
+    pub fn quest_reward(&self, quest_id: U256) -> U256 {
+        self.achievements.reward_of(quest_id)
+    }
+
+    pub fn quest_attester(&self) -> Address {
+        self.achievements.attester()
+    }
+
+    pub fn is_quest_completed(&self, quest_id: U256, account: Address) -> bool {
+        self.achievements.is_completed(quest_id, account)
+    }
+
+    pub fn is_quest_claimed(&self, quest_id: U256, account: Address) -> bool {
+        self.achievements.is_claimed(quest_id, account)
+    }
+
+    pub fn define_quest(&mut self, quest_id: U256, reward: U256) -> Result<(), Error> {
+        self._define_quest(quest_id, reward)
+    }
+
+    pub fn set_quest_attester(&mut self, attester: Address) -> Result<(), Error> {
+        self._set_quest_attester(attester)
+    }
+
+    pub fn complete_quest(&mut self, quest_id: U256, account: Address) -> Result<(), Error> {
+        self._complete_quest(quest_id, account)
+    }
+
+    pub fn claim_quest_reward(&mut self, quest_id: U256) -> Result<U256, Error> {
+        self._claim_quest_reward(quest_id)
+    }
+
+    #[selector(name = "contractURI")]
+    pub fn contract_uri(&self) -> String {
+        self.contract_uri.get_string()
+    }
+
+    #[selector(name = "setContractURI")]
+    pub fn set_contract_uri(&mut self, uri: String) -> Result<(), Error> {
+        self._set_contract_uri(uri)
+    }
+
+    pub fn multicall(&mut self, data: Vec<Bytes>) -> Result<Vec<Bytes>, Vec<u8>> {
+        self._multicall(data)
+    }
+
+    pub fn l2_gateway(&self) -> Address {
+        self.l2_gateway.get()
+    }
+
+    pub fn set_bridge(&mut self, l2_gateway: Address, l1_address: Address) -> Result<(), Error> {
+        self._set_bridge(l2_gateway, l1_address)
+    }
+
+    pub fn recover_tokens(&mut self, to: Address) -> Result<U256, Error> {
+        self._recover_tokens(to)
+    }
 }
 
 impl RngToken {
-    fn _init(&mut self, initial_owner: Address) -> Result<(), Error> {
+    fn _init(
+        &mut self,
+        initial_owner: Address,
+        cap: U256,
+        allocations: Vec<(Address, U256)>,
+    ) -> Result<(), Error> {
         self.ownable.constructor(initial_owner)?;
         self.metadata
             .constructor(String::from("Lucky Token"), String::from("LCK"));
+        // The cap has no setter: it is fixed for the lifetime of the
+        // deployment, so holders can rely on it as the worst case.
+        self.capped.constructor(cap)?;
+        for (account, value) in allocations {
+            self._mint_capped(account, value)?;
+        }
         Ok(())
     }
 
     fn _mint(&mut self, account: Address, value: U256) -> Result<(), Error> {
         self.ownable.only_owner()?;
+        self._mint_capped(account, value)
+    }
+
+    /// Mints `value` to `account` unless it would take the tokens ever
+    /// issued on L2 above the cap. Every ERC-20 mint except
+    /// [`IArbToken::bridge_mint`] goes through here.
+    ///
+    /// Issuance is counted separately from the total supply because burns,
+    /// including withdrawals to L1, must not free room under the cap. Tokens
+    /// on L1 only exist once withdrawn from here, so the supply on both
+    /// layers together never exceeds the cap.
+    fn _mint_capped(&mut self, account: Address, value: U256) -> Result<(), Error> {
+        let cap = self.capped.cap();
+        let increased_supply = self.issued.get().saturating_add(value);
+        if increased_supply > cap {
+            return Err(Error::ExceededCap(capped::ERC20ExceededCap {
+                increased_supply,
+                cap,
+            }));
+        }
+        self.issued.set(increased_supply);
         self.erc20._mint(account, value)?;
         Ok(())
     }
+
+    fn _mint_id(&mut self, to: Address, id: U256, amount: U256) -> Result<(), Error> {
+        self.ownable.only_owner()?;
+        self.erc6909._mint(to, id, amount)?;
+        Ok(())
+    }
+
+    fn _set_soulbound(&mut self, enabled: bool) -> Result<(), Error> {
+        self.ownable.only_owner()?;
+        self.soulbound.set(enabled);
+        self.vm().log(SoulboundModeUpdated { enabled });
+        Ok(())
+    }
+
+    fn _transfer_with_memo(
+        &mut self,
+        to: Address,
+        value: U256,
+        memo: Bytes,
+    ) -> Result<bool, Error> {
+        IErc20::transfer(self, to, value)?;
+        let from = self.vm().msg_sender();
+        self.vm().log(TransferMemo {
+            from,
+            to,
+            value,
+            memo: memo.0.into(),
+        });
+        Ok(true)
+    }
+
+    fn _transfer_batch(&mut self, transfers: Vec<(Address, U256)>) -> Result<bool, Error> {
+        for (to, value) in transfers {
+            IErc20::transfer(self, to, value)?;
+        }
+        Ok(true)
+    }
+
+    fn _transfer_from_batch(
+        &mut self,
+        from: Address,
+        transfers: Vec<(Address, U256)>,
+    ) -> Result<bool, Error> {
+        for (to, value) in transfers {
+            IErc20::transfer_from(self, from, to, value)?;
+        }
+        Ok(true)
+    }
+
+    fn _recover_tokens(&mut self, to: Address) -> Result<U256, Error> {
+        self.ownable.only_owner()?;
+        // The contract holds no escrow or treasury of its own, so its whole
+        // balance arrived by mistake and is surplus.
+        let contract = self.vm().contract_address();
+        let amount = self.erc20.balance_of(contract);
+        self.erc20._transfer(contract, to, amount)?;
+        self.vm().log(TokensRecovered { to, amount });
+        Ok(amount)
+    }
+
+    fn _check_transferable(&self) -> Result<(), Error> {
+        if self.soulbound.get() {
+            return Err(Error::Soulbound(TokenSoulbound {}));
+        }
+        Ok(())
+    }
+
+    fn _approve_with_deadline(
+        &mut self,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Result<bool, Error> {
+        self.erc20.approve(spender, value)?;
+        let owner = self.vm().msg_sender();
+        self.allowance_deadlines
+            .setter(owner)
+            .setter(spender)
+            .set(deadline);
+        self.vm().log(ApprovalDeadlineSet {
+            owner,
+            spender,
+            deadline,
+        });
+        Ok(true)
+    }
+
+    /// Adjusts the caller's allowance for `spender` in place. Any deadline set
+    /// by [`Self::approve_with_deadline`] is kept.
+    fn _increase_allowance(&mut self, spender: Address, added_value: U256) -> Result<bool, Error> {
+        let owner = self.vm().msg_sender();
+        let current = self.erc20.allowance(owner, spender);
+        let Some(allowance) = current.checked_add(added_value) else {
+            return Err(Error::AllowanceOverflow(ERC20AllowanceOverflow {
+                spender,
+                current_allowance: current,
+                requested_increase: added_value,
+            }));
+        };
+        Ok(self.erc20.approve(spender, allowance)?)
+    }
+
+    fn _decrease_allowance(
+        &mut self,
+        spender: Address,
+        subtracted_value: U256,
+    ) -> Result<bool, Error> {
+        let owner = self.vm().msg_sender();
+        let current = self.erc20.allowance(owner, spender);
+        if current < subtracted_value {
+            return Err(Error::FailedDecreaseAllowance(
+                ERC20FailedDecreaseAllowance {
+                    spender,
+                    current_allowance: current,
+                    requested_decrease: subtracted_value,
+                },
+            ));
+        }
+        Ok(self.erc20.approve(spender, current - subtracted_value)?)
+    }
+
+    /// Reverts if the caller's allowance over `owner`'s tokens has expired.
+    /// A zero deadline means the allowance never expires.
+    fn _check_allowance_deadline(&self, owner: Address) -> Result<(), Error> {
+        let spender = self.vm().msg_sender();
+        let deadline = self.allowance_deadlines.get(owner).get(spender);
+        if !deadline.is_zero() && U256::from(self.vm().block_timestamp()) > deadline {
+            return Err(Error::AllowanceExpired(AllowanceExpired {
+                spender,
+                deadline,
+            }));
+        }
+        Ok(())
+    }
+
+    /// Drops any expiry left over from [`Self::approve_with_deadline`] once
+    /// `owner` approves `spender` without one.
+    fn _clear_allowance_deadline(&mut self, owner: Address, spender: Address) {
+        if !self.allowance_deadlines.get(owner).get(spender).is_zero() {
+            self.allowance_deadlines.setter(owner).delete(spender);
+        }
+    }
+
+    fn _define_quest(&mut self, quest_id: U256, reward: U256) -> Result<(), Error> {
+        self.ownable.only_owner()?;
+        self.achievements._define(quest_id, reward);
+        Ok(())
+    }
+
+    fn _set_quest_attester(&mut self, attester: Address) -> Result<(), Error> {
+        self.ownable.only_owner()?;
+        self.achievements._set_attester(attester);
+        Ok(())
+    }
+
+    fn _complete_quest(&mut self, quest_id: U256, account: Address) -> Result<(), Error> {
+        Ok(self.achievements._complete(quest_id, account)?)
+    }
+
+    fn _claim_quest_reward(&mut self, quest_id: U256) -> Result<U256, Error> {
+        let account = self.vm().msg_sender();
+        let reward = self.achievements._claim(quest_id, account)?;
+        self._mint_capped(account, reward)?;
+        Ok(reward)
+    }
+
+    fn _set_contract_uri(&mut self, uri: String) -> Result<(), Error> {
+        self.ownable.only_owner()?;
+        self.contract_uri.set_str(uri);
+        self.vm().log(ContractURIUpdated {});
+        Ok(())
+    }
+
+    /// Dispatches each entry of `data` to this contract's own router, so every
+    /// call runs with the original caller as `msg_sender`. Reverts with the
+    /// revert data of the first failing call, or with a multicall error if a
+    /// call selects no function.
+    fn _multicall(&mut self, data: Vec<Bytes>) -> Result<Vec<Bytes>, Vec<u8>> {
+        let mut results = Vec::with_capacity(data.len());
+        for (index, call) in data.into_iter().enumerate() {
+            let index = U256::from(index);
+            let Some((selector, input)) = call.split_first_chunk::<4>() else {
+                return Err(Error::InvalidMulticall(MulticallInvalidCall { index }).into());
+            };
+            let Some(result) =
+                <Self as Router<Self>>::route(self, u32::from_be_bytes(*selector), input)
+            else {
+                return Err(Error::UnknownMulticallSelector(MulticallUnknownSelector {
+                    index,
+                    selector: (*selector).into(),
+                })
+                .into());
+            };
+            results.push(Bytes::from(result?));
+        }
+        Ok(results)
+    }
+
+    fn _set_bridge(&mut self, l2_gateway: Address, l1_address: Address) -> Result<(), Error> {
+        self.ownable.only_owner()?;
+        // The gateway can mint and burn any balance, so it is set once:
+        // re-pointing it would let the owner burn holders' tokens. It must
+        // also be a contract, so the owner cannot name their own account.
+        let current = self.l2_gateway.get();
+        if !current.is_zero() {
+            return Err(Error::BridgeAlreadyConfigured(BridgeAlreadyConfigured {
+                gateway: current,
+                l1Token: self.l1_address.get(),
+            }));
+        }
+        if l1_address.is_zero() || self.vm().code_size(l2_gateway) == 0 {
+            return Err(Error::InvalidBridgeConfiguration(
+                BridgeInvalidConfiguration {
+                    gateway: l2_gateway,
+                    l1Token: l1_address,
+                },
+            ));
+        }
+        self.l2_gateway.set(l2_gateway);
+        self.l1_address.set(l1_address);
+        self.vm().log(BridgeConfigured {
+            gateway: l2_gateway,
+            l1Token: l1_address,
+        });
+        Ok(())
+    }
+
+    fn _only_gateway(&self) -> Result<(), Error> {
+        let caller = self.vm().msg_sender();
+        if caller != self.l2_gateway.get() {
+            return Err(Error::UnauthorizedGateway(BridgeUnauthorizedGateway {
+                caller,
+            }));
+        }
+        Ok(())
+    }
 }
 
 //*//////////////////////////////////////////////////////////////////////////
@@ -111,6 +667,7 @@ impl IErc20 for RngToken {
     }
 
     fn transfer(&mut self, to: Address, value: U256) -> Result<bool, Self::Error> {
+        self._check_transferable()?;
         Ok(self.erc20.transfer(to, value)?)
     }
 
@@ -119,7 +676,10 @@ impl IErc20 for RngToken {
     }
 
     fn approve(&mut self, spender: Address, value: U256) -> Result<bool, Self::Error> {
-        Ok(self.erc20.approve(spender, value)?)
+        self.erc20.approve(spender, value)?;
+        let owner = self.vm().msg_sender();
+        self._clear_allowance_deadline(owner, spender);
+        Ok(true)
     }
 
     fn transfer_from(
@@ -128,6 +688,8 @@ impl IErc20 for RngToken {
         to: Address,
         value: U256,
     ) -> Result<bool, Self::Error> {
+        self._check_transferable()?;
+        self._check_allowance_deadline(from)?;
         Ok(self.erc20.transfer_from(from, to, value)?)
     }
 }
@@ -147,6 +709,13 @@ impl IErc20Metadata for RngToken {
     }
 }
 
+#[public]
+impl ICapped for RngToken {
+    fn cap(&self) -> U256 {
+        self.capped.cap()
+    }
+}
+
 #[public]
 impl IOwnable for RngToken {
     type Error = Error;
@@ -169,5 +738,663 @@ impl IErc165 for RngToken {
     fn supports_interface(&self, interface_id: B32) -> bool {
         Erc20::supports_interface(&self.erc20, interface_id)
             || Erc20Metadata::supports_interface(&self.metadata, interface_id)
+            || Erc6909::supports_interface(&self.erc6909, interface_id)
+    }
+}
+
+//*//////////////////////////////////////////////////////////////////////////
+//                               ERC6909 SETUP
+//////////////////////////////////////////////////////////////////////////*//
+
+#[public]
+impl IErc6909 for RngToken {
+    type Error = Error;
+
+    fn balance_of(&self, owner: Address, id: U256) -> U256 {
+        self.erc6909.balance_of(owner, id)
+    }
+
+    fn allowance(&self, owner: Address, spender: Address, id: U256) -> U256 {
+        self.erc6909.allowance(owner, spender, id)
+    }
+
+    fn is_operator(&self, owner: Address, spender: Address) -> bool {
+        self.erc6909.is_operator(owner, spender)
+    }
+
+    fn transfer(&mut self, receiver: Address, id: U256, amount: U256) -> Result<bool, Self::Error> {
+        Ok(self.erc6909.transfer(receiver, id, amount)?)
+    }
+
+    fn transfer_from(
+        &mut self,
+        sender: Address,
+        receiver: Address,
+        id: U256,
+        amount: U256,
+    ) -> Result<bool, Self::Error> {
+        Ok(self.erc6909.transfer_from(sender, receiver, id, amount)?)
+    }
+
+    fn approve(&mut self, spender: Address, id: U256, amount: U256) -> Result<bool, Self::Error> {
+        Ok(self.erc6909.approve(spender, id, amount)?)
+    }
+
+    fn set_operator(&mut self, spender: Address, approved: bool) -> Result<bool, Self::Error> {
+        Ok(self.erc6909.set_operator(spender, approved)?)
+    }
+}
+
+//*//////////////////////////////////////////////////////////////////////////
+//                              ARBITRUM BRIDGE
+//////////////////////////////////////////////////////////////////////////*//
+
+/// Hooks the canonical Arbitrum token bridge calls on an L2 custom token.
+trait IArbToken {
+    type Error: Into<Vec<u8>>;
+
+    fn bridge_mint(&mut self, account: Address, amount: U256) -> Result<(), Self::Error>;
+
+    fn bridge_burn(&mut self, account: Address, amount: U256) -> Result<(), Self::Error>;
+
+    fn l1_address(&self) -> Address;
+}
+
+#[public]
+impl IArbToken for RngToken {
+    type Error = Error;
+
+    /// Not counted against the cap: deposited tokens were issued here and
+    /// counted when first minted, before being withdrawn to L1.
+    fn bridge_mint(&mut self, account: Address, amount: U256) -> Result<(), Self::Error> {
+        self._only_gateway()?;
+        Ok(self.erc20._mint(account, amount)?)
+    }
+
+    fn bridge_burn(&mut self, account: Address, amount: U256) -> Result<(), Self::Error> {
+        self._only_gateway()?;
+        Ok(self.erc20._burn(account, amount)?)
+    }
+
+    fn l1_address(&self) -> Address {
+        self.l1_address.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use stylus_sdk::{
+        alloy_primitives::address,
+        alloy_sol_types::{SolError, SolEvent, SolValue},
+        crypto::keccak,
+        testing::*,
+    };
+
+    use super::*;
+
+    const OWNER: Address = address!("0000000000000000000000000000000000000a11");
+    const ALICE: Address = address!("00000000000000000000000000000000000a1ce0");
+    const BOB: Address = address!("0000000000000000000000000000000000000b0b");
+    const CAP: U256 = U256::from_limbs([1_000_000, 0, 0, 0]);
+
+    fn deploy(vm: &TestVM) -> RngToken {
+        let mut token = RngToken::from(vm);
+        token
+            .constructor(OWNER, CAP, vec![(ALICE, U256::from(1_000))])
+            .expect("should deploy");
+        token
+    }
+
+    #[test]
+    fn constructor_rejects_zero_cap() {
+        let vm = TestVM::default();
+        let mut token = RngToken::from(&vm);
+
+        let err = token.constructor(OWNER, U256::ZERO, vec![]).unwrap_err();
+        assert!(matches!(err, Error::InvalidCap(_)));
+    }
+
+    #[test]
+    fn mint_is_capped() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(OWNER);
+
+        assert_eq!(ICapped::cap(&token), CAP);
+        token.mint(BOB, CAP - U256::from(1_000)).unwrap();
+        let err = token.mint(BOB, U256::ONE).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::ExceededCap(capped::ERC20ExceededCap { increased_supply, cap })
+                if increased_supply == CAP + U256::ONE && cap == CAP
+        ));
+        assert_eq!(IErc20::total_supply(&token), CAP);
+    }
+
+    #[test]
+    fn cap_holds_across_bridge_round_trip() {
+        let vm = TestVM::default();
+        let mut token = deploy_bridged(&vm);
+        let remaining = CAP - U256::from(1_000);
+        token.mint(BOB, remaining).unwrap();
+
+        // Withdrawing to L1 burns supply but not issuance, so it frees no
+        // room under the cap.
+        vm.set_sender(GATEWAY);
+        token.bridge_burn(BOB, remaining).unwrap();
+        vm.set_sender(OWNER);
+        let err = token.mint(BOB, remaining).unwrap_err();
+        assert!(matches!(err, Error::ExceededCap(_)));
+
+        // Depositing back restores the withdrawn tokens, still within the
+        // cap.
+        vm.set_sender(GATEWAY);
+        token.bridge_mint(BOB, remaining).unwrap();
+        assert_eq!(IErc20::total_supply(&token), CAP);
+    }
+
+    #[test]
+    fn increase_allowance_adds_and_keeps_deadline() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        token
+            .approve_with_deadline(BOB, U256::from(10), U256::from(500))
+            .unwrap();
+        assert!(token.increase_allowance(BOB, U256::from(5)).unwrap());
+
+        assert_eq!(IErc20::allowance(&token, ALICE, BOB), U256::from(15));
+        assert_eq!(token.allowance_deadline(ALICE, BOB), U256::from(500));
+    }
+
+    #[test]
+    fn increase_allowance_reverts_on_overflow() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        IErc20::approve(&mut token, BOB, U256::MAX).unwrap();
+        let err = token.increase_allowance(BOB, U256::ONE).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::AllowanceOverflow(ERC20AllowanceOverflow {
+                spender,
+                current_allowance,
+                requested_increase,
+            }) if spender == BOB && current_allowance == U256::MAX && requested_increase == U256::ONE
+        ));
+        assert_eq!(IErc20::allowance(&token, ALICE, BOB), U256::MAX);
+    }
+
+    #[test]
+    fn decrease_allowance_subtracts_and_keeps_deadline() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        token
+            .approve_with_deadline(BOB, U256::from(10), U256::from(500))
+            .unwrap();
+        assert!(token.decrease_allowance(BOB, U256::from(10)).unwrap());
+
+        assert_eq!(IErc20::allowance(&token, ALICE, BOB), U256::ZERO);
+        assert_eq!(token.allowance_deadline(ALICE, BOB), U256::from(500));
+    }
+
+    #[test]
+    fn decrease_allowance_below_zero_reverts() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        IErc20::approve(&mut token, BOB, U256::from(3)).unwrap();
+        let err = token.decrease_allowance(BOB, U256::from(4)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::FailedDecreaseAllowance(ERC20FailedDecreaseAllowance {
+                spender,
+                current_allowance,
+                requested_decrease,
+            }) if spender == BOB && current_allowance == U256::from(3) && requested_decrease == U256::from(4)
+        ));
+        assert_eq!(IErc20::allowance(&token, ALICE, BOB), U256::from(3));
+    }
+
+    #[test]
+    fn approve_clears_deadline() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        token
+            .approve_with_deadline(BOB, U256::from(10), U256::from(500))
+            .unwrap();
+        IErc20::approve(&mut token, BOB, U256::from(20)).unwrap();
+
+        assert_eq!(token.allowance_deadline(ALICE, BOB), U256::ZERO);
+    }
+
+    #[test]
+    fn expired_allowance_cannot_be_spent() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+        token
+            .approve_with_deadline(BOB, U256::from(10), U256::from(500))
+            .unwrap();
+
+        vm.set_sender(BOB);
+        vm.set_block_timestamp(500);
+        assert!(IErc20::transfer_from(&mut token, ALICE, BOB, U256::from(4)).unwrap());
+
+        vm.set_block_timestamp(501);
+        let err = IErc20::transfer_from(&mut token, ALICE, BOB, U256::from(4)).unwrap_err();
+        assert!(matches!(err, Error::AllowanceExpired(_)));
+    }
+
+    const GATEWAY: Address = address!("0000000000000000000000000000000000000ca7");
+    const L1_TOKEN: Address = address!("00000000000000000000000000000000000001e1");
+
+    fn deploy_bridged(vm: &TestVM) -> RngToken {
+        let mut token = deploy(vm);
+        vm.set_code(GATEWAY, vec![0xfe]);
+        vm.set_sender(OWNER);
+        token.set_bridge(GATEWAY, L1_TOKEN).unwrap();
+        token
+    }
+
+    #[test]
+    fn set_bridge_configures_once() {
+        let vm = TestVM::default();
+        let mut token = deploy_bridged(&vm);
+
+        assert_eq!(token.l2_gateway(), GATEWAY);
+        assert_eq!(token.l1_address(), L1_TOKEN);
+
+        let other = address!("0000000000000000000000000000000000000ca8");
+        vm.set_code(other, vec![0xfe]);
+        let err = token.set_bridge(other, L1_TOKEN).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::BridgeAlreadyConfigured(BridgeAlreadyConfigured { gateway, l1Token })
+                if gateway == GATEWAY && l1Token == L1_TOKEN
+        ));
+        assert_eq!(token.l2_gateway(), GATEWAY);
+    }
+
+    #[test]
+    fn set_bridge_is_owner_only() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_code(GATEWAY, vec![0xfe]);
+        vm.set_sender(ALICE);
+
+        let err = token.set_bridge(GATEWAY, L1_TOKEN).unwrap_err();
+        assert!(matches!(err, Error::UnauthorizedAccount(_)));
+    }
+
+    #[test]
+    fn set_bridge_rejects_invalid_configuration() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_code(GATEWAY, vec![0xfe]);
+        vm.set_sender(OWNER);
+
+        for (gateway, l1_token) in [
+            (Address::ZERO, L1_TOKEN),
+            (GATEWAY, Address::ZERO),
+            // An account without code, e.g. the owner's own.
+            (OWNER, L1_TOKEN),
+        ] {
+            let err = token.set_bridge(gateway, l1_token).unwrap_err();
+            assert!(matches!(err, Error::InvalidBridgeConfiguration(_)));
+        }
+        assert_eq!(token.l2_gateway(), Address::ZERO);
+    }
+
+    #[test]
+    fn bridge_hooks_reject_other_callers() {
+        let vm = TestVM::default();
+        let mut token = deploy_bridged(&vm);
+        vm.set_sender(OWNER);
+
+        let err = token.bridge_mint(BOB, U256::ONE).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnauthorizedGateway(BridgeUnauthorizedGateway { caller }) if caller == OWNER
+        ));
+        let err = token.bridge_burn(ALICE, U256::ONE).unwrap_err();
+        assert!(matches!(err, Error::UnauthorizedGateway(_)));
+        assert_eq!(IErc20::balance_of(&token, ALICE), U256::from(1_000));
+    }
+
+    #[test]
+    fn bridge_burn_lowers_balance_without_allowance() {
+        let vm = TestVM::default();
+        let mut token = deploy_bridged(&vm);
+        vm.set_sender(GATEWAY);
+
+        token.bridge_burn(ALICE, U256::from(400)).unwrap();
+        assert_eq!(IErc20::balance_of(&token, ALICE), U256::from(600));
+        assert_eq!(IErc20::total_supply(&token), U256::from(600));
+
+        let err = token.bridge_burn(ALICE, U256::from(601)).unwrap_err();
+        assert!(matches!(err, Error::InsufficientBalance(_)));
+    }
+
+    #[test]
+    fn set_soulbound_is_owner_only_and_emits() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+
+        vm.set_sender(ALICE);
+        let err = token.set_soulbound(true).unwrap_err();
+        assert!(matches!(err, Error::UnauthorizedAccount(_)));
+        assert!(!token.soulbound());
+
+        vm.set_sender(OWNER);
+        token.set_soulbound(true).unwrap();
+        assert!(token.soulbound());
+        let logs = vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(topics[0], SoulboundModeUpdated::SIGNATURE_HASH);
+        assert_eq!(*data, true.abi_encode());
+    }
+
+    #[test]
+    fn soulbound_mode_blocks_transfers() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+        IErc20::approve(&mut token, BOB, U256::from(100)).unwrap();
+        vm.set_sender(OWNER);
+        token.set_soulbound(true).unwrap();
+
+        vm.set_sender(ALICE);
+        let err = IErc20::transfer(&mut token, BOB, U256::ONE).unwrap_err();
+        assert!(matches!(err, Error::Soulbound(_)));
+        let err = token
+            .transfer_with_memo(BOB, U256::ONE, Bytes::from(vec![1]))
+            .unwrap_err();
+        assert!(matches!(err, Error::Soulbound(_)));
+        let err = token.transfer_batch(vec![(BOB, U256::ONE)]).unwrap_err();
+        assert!(matches!(err, Error::Soulbound(_)));
+
+        vm.set_sender(BOB);
+        let err = IErc20::transfer_from(&mut token, ALICE, BOB, U256::ONE).unwrap_err();
+        assert!(matches!(err, Error::Soulbound(_)));
+        let err = token
+            .transfer_from_batch(ALICE, vec![(BOB, U256::ONE)])
+            .unwrap_err();
+        assert!(matches!(err, Error::Soulbound(_)));
+        assert_eq!(IErc20::balance_of(&token, BOB), U256::ZERO);
+
+        vm.set_sender(OWNER);
+        token.set_soulbound(false).unwrap();
+
+        vm.set_sender(ALICE);
+        IErc20::transfer(&mut token, BOB, U256::ONE).unwrap();
+        token
+            .transfer_with_memo(BOB, U256::ONE, Bytes::from(vec![1]))
+            .unwrap();
+        token.transfer_batch(vec![(BOB, U256::ONE)]).unwrap();
+        vm.set_sender(BOB);
+        IErc20::transfer_from(&mut token, ALICE, BOB, U256::ONE).unwrap();
+        token
+            .transfer_from_batch(ALICE, vec![(BOB, U256::ONE)])
+            .unwrap();
+        assert_eq!(IErc20::balance_of(&token, BOB), U256::from(5));
+    }
+
+    /// Encodes a call to `signature` with ABI-encoded `params`.
+    fn call(signature: &str, params: Vec<u8>) -> Bytes {
+        let mut call = keccak(signature.as_bytes())[..4].to_vec();
+        call.extend(params);
+        Bytes::from(call)
+    }
+
+    #[test]
+    fn multicall_batches_calls_as_the_caller() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        let results = token
+            .multicall(vec![
+                call(
+                    "approve(address,uint256)",
+                    (BOB, U256::from(10)).abi_encode_params(),
+                ),
+                call(
+                    "transfer(address,uint256)",
+                    (BOB, U256::from(30)).abi_encode_params(),
+                ),
+                call("balanceOf(address)", BOB.abi_encode()),
+            ])
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, true.abi_encode());
+        assert_eq!(results[1].0, true.abi_encode());
+        assert_eq!(results[2].0, U256::from(30).abi_encode());
+        assert_eq!(IErc20::allowance(&token, ALICE, BOB), U256::from(10));
+        assert_eq!(IErc20::balance_of(&token, ALICE), U256::from(970));
+    }
+
+    #[test]
+    fn multicall_passes_revert_data_through() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        let err = token
+            .multicall(vec![call(
+                "transfer(address,uint256)",
+                (BOB, U256::from(1_001)).abi_encode_params(),
+            )])
+            .unwrap_err();
+        let expected = erc20::ERC20InsufficientBalance {
+            sender: ALICE,
+            balance: U256::from(1_000),
+            needed: U256::from(1_001),
+        };
+        assert_eq!(err, expected.abi_encode());
+    }
+
+    #[test]
+    fn multicall_rejects_calls_selecting_nothing() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+        let balance_of = call("balanceOf(address)", ALICE.abi_encode());
+
+        let err = token
+            .multicall(vec![balance_of.clone(), Bytes::from(vec![0xde, 0xad])])
+            .unwrap_err();
+        let expected = MulticallInvalidCall { index: U256::ONE };
+        assert_eq!(err, expected.abi_encode());
+
+        let err = token
+            .multicall(vec![balance_of, Bytes::from(vec![0xde, 0xad, 0xbe, 0xef])])
+            .unwrap_err();
+        let expected = MulticallUnknownSelector {
+            index: U256::ONE,
+            selector: [0xde, 0xad, 0xbe, 0xef].into(),
+        };
+        assert_eq!(err, expected.abi_encode());
+    }
+
+    #[test]
+    fn recover_tokens_sweeps_contract_balance() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        let contract = vm.contract_address();
+        vm.set_sender(ALICE);
+        IErc20::transfer(&mut token, contract, U256::from(250)).unwrap();
+
+        vm.set_sender(OWNER);
+        assert_eq!(token.recover_tokens(BOB).unwrap(), U256::from(250));
+        assert_eq!(IErc20::balance_of(&token, contract), U256::ZERO);
+        assert_eq!(IErc20::balance_of(&token, BOB), U256::from(250));
+
+        let logs = vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(
+            *topics,
+            vec![TokensRecovered::SIGNATURE_HASH, BOB.into_word()]
+        );
+        assert_eq!(*data, U256::from(250).abi_encode());
+    }
+
+    #[test]
+    fn recover_tokens_is_owner_only() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        let err = token.recover_tokens(ALICE).unwrap_err();
+        assert!(matches!(err, Error::UnauthorizedAccount(_)));
+    }
+
+    #[test]
+    fn recover_tokens_rejects_zero_receiver() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(OWNER);
+
+        let err = token.recover_tokens(Address::ZERO).unwrap_err();
+        assert!(matches!(err, Error::InvalidReceiver(_)));
+    }
+
+    #[test]
+    fn transfer_with_memo_moves_and_logs_memo() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        vm.set_sender(ALICE);
+
+        assert!(token
+            .transfer_with_memo(BOB, U256::from(40), Bytes::from(b"invoice 7".to_vec()))
+            .unwrap());
+
+        assert_eq!(IErc20::balance_of(&token, ALICE), U256::from(960));
+        assert_eq!(IErc20::balance_of(&token, BOB), U256::from(40));
+        let logs = vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        let expected = TransferMemo {
+            from: ALICE,
+            to: BOB,
+            value: U256::from(40),
+            memo: b"invoice 7".to_vec().into(),
+        };
+        assert_eq!(
+            *topics,
+            vec![
+                TransferMemo::SIGNATURE_HASH,
+                ALICE.into_word(),
+                BOB.into_word()
+            ]
+        );
+        assert_eq!(*data, expected.encode_data());
+    }
+
+    #[test]
+    fn transfer_batch_pays_each_recipient() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        let carol = address!("00000000000000000000000000000000000ca201");
+        vm.set_sender(ALICE);
+
+        assert!(token
+            .transfer_batch(vec![(BOB, U256::from(100)), (carol, U256::from(200))])
+            .unwrap());
+        assert_eq!(IErc20::balance_of(&token, ALICE), U256::from(700));
+        assert_eq!(IErc20::balance_of(&token, BOB), U256::from(100));
+        assert_eq!(IErc20::balance_of(&token, carol), U256::from(200));
+
+        let err = token
+            .transfer_batch(vec![(BOB, U256::from(701))])
+            .unwrap_err();
+        assert!(matches!(err, Error::InsufficientBalance(_)));
+    }
+
+    #[test]
+    fn transfer_from_batch_spends_allowance_across_entries() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        let carol = address!("00000000000000000000000000000000000ca201");
+        vm.set_sender(ALICE);
+        IErc20::approve(&mut token, BOB, U256::from(300)).unwrap();
+
+        vm.set_sender(BOB);
+        assert!(token
+            .transfer_from_batch(
+                ALICE,
+                vec![(BOB, U256::from(100)), (carol, U256::from(150))]
+            )
+            .unwrap());
+        assert_eq!(IErc20::allowance(&token, ALICE, BOB), U256::from(50));
+        assert_eq!(IErc20::balance_of(&token, carol), U256::from(150));
+
+        let err = token
+            .transfer_from_batch(ALICE, vec![(carol, U256::from(51))])
+            .unwrap_err();
+        assert!(matches!(err, Error::InsufficientAllowance(_)));
+    }
+
+    #[test]
+    fn set_contract_uri_is_owner_only_and_emits() {
+        let vm = TestVM::default();
+        let mut token = deploy(&vm);
+        let uri = String::from("ipfs://contract.json");
+
+        vm.set_sender(ALICE);
+        let err = token.set_contract_uri(uri.clone()).unwrap_err();
+        assert!(matches!(err, Error::UnauthorizedAccount(_)));
+        assert_eq!(token.contract_uri(), "");
+
+        vm.set_sender(OWNER);
+        token.set_contract_uri(uri.clone()).unwrap();
+        assert_eq!(token.contract_uri(), uri);
+        let logs = vm.get_emitted_logs();
+        let (topics, _) = logs.last().unwrap();
+        assert_eq!(*topics, vec![ContractURIUpdated::SIGNATURE_HASH]);
+    }
+
+    #[test]
+    fn constructor_mints_allocations() {
+        let vm = TestVM::default();
+        let token = deploy(&vm);
+
+        assert_eq!(IErc20::balance_of(&token, ALICE), U256::from(1_000));
+        assert_eq!(IErc20::total_supply(&token), U256::from(1_000));
+        let logs = vm.get_emitted_logs();
+        let (topics, data) = logs.last().unwrap();
+        assert_eq!(
+            *topics,
+            vec![
+                erc20::Transfer::SIGNATURE_HASH,
+                Address::ZERO.into_word(),
+                ALICE.into_word()
+            ]
+        );
+        assert_eq!(*data, U256::from(1_000).abi_encode());
+    }
+
+    #[test]
+    fn constructor_rejects_allocations_over_cap() {
+        let vm = TestVM::default();
+        let mut token = RngToken::from(&vm);
+
+        let err = token
+            .constructor(OWNER, CAP, vec![(ALICE, CAP), (BOB, U256::ONE)])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::ExceededCap(capped::ERC20ExceededCap { increased_supply, cap })
+                if increased_supply == CAP + U256::ONE && cap == CAP
+        ));
     }
 }
@@ -7,4 +7,7 @@ pub extern "C" fn main() {}
 #[cfg(feature = "export-abi")]
 fn main() {
     rng_token::print_from_args();
+    if std::env::args().nth(1).as_deref() != Some("constructor") {
+        rng_token::abi::print_events_and_errors();
+    }
 }
@@ -0,0 +1,234 @@
+//! Helpers to derive many random values from a single VRF word.
+//!
+//! Requesting one word from the oracle and expanding it locally is much
+//! cheaper than asking for a large `rng_count`. Derived words are
+//! `keccak256(seed ‖ index)`, so they are independent for practical purposes
+//! and anyone can recompute them from the delivered seed.
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use stylus_sdk::{alloy_primitives::U256, crypto::keccak};
+
+/// Returns the `index`-th word derived from `seed`.
+pub fn derive(seed: U256, index: U256) -> U256 {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(&seed.to_be_bytes::<32>());
+    buf[32..].copy_from_slice(&index.to_be_bytes::<32>());
+    U256::from_be_bytes(keccak(buf).0)
+}
+
+/// Expands `seed` into `n` words, `derive(seed, 0)..derive(seed, n - 1)`.
+pub fn expand(seed: U256, n: usize) -> Vec<U256> {
+    (0..n).map(|i| derive(seed, U256::from(i))).collect()
+}
+
+/// Maps `word` to a value in `[low, high]` without modulo bias.
+///
+/// Words falling in the biased tail are rehashed until they land in the
+/// uniform zone, which happens with probability below `range / 2^256`.
+///
+/// # Panics
+///
+/// * If `low` is greater than `high`.
+pub fn uniform(word: U256, low: U256, high: U256) -> U256 {
+    assert!(low <= high, "empty range");
+
+    let range = (high - low).wrapping_add(U256::ONE);
+    // `low..=high` spans the whole domain.
+    if range.is_zero() {
+        return word;
+    }
+
+    // Number of values at the bottom of the domain that would skew the
+    // modulo: `2^256 % range`.
+    let threshold = range.wrapping_neg() % range;
+    let mut word = word;
+    while word < threshold {
+        word = U256::from_be_bytes(keccak(word.to_be_bytes::<32>()).0);
+    }
+    low + word % range
+}
+
+/// Shuffles `items` in place with a Fisher–Yates shuffle driven by `seed`.
+pub fn shuffle<T>(seed: U256, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        let word = derive(seed, U256::from(i));
+        let j = uniform(word, U256::ZERO, U256::from(i)).to::<usize>();
+        items.swap(i, j);
+    }
+}
+
+/// Selects `k` distinct indices from `0..n` in random order, using a partial
+/// Fisher–Yates shuffle driven by `seed`.
+///
+/// Only the `k` swapped positions are materialized, so the cost is bounded
+/// by `k` rather than by the population size `n`.
+///
+/// # Panics
+///
+/// * If `k` is greater than `n`.
+pub fn sample_indices(seed: U256, n: usize, k: usize) -> Vec<usize> {
+    assert!(k <= n, "sample larger than population");
+
+    // Positions of the virtual `0..n` array that were swapped away from their
+    // identity value.
+    let mut swapped: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut picked = Vec::with_capacity(k);
+    for i in 0..k {
+        let word = derive(seed, U256::from(i));
+        let j = uniform(word, U256::from(i), U256::from(n - 1)).to::<usize>();
+
+        let at_i = swapped.get(&i).copied().unwrap_or(i);
+        let at_j = swapped.get(&j).copied().unwrap_or(j);
+        swapped.insert(j, at_i);
+        picked.push(at_j);
+    }
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+    use stylus_sdk::alloy_primitives::U256;
+
+    use super::*;
+
+    const TRIALS: usize = 10_000;
+
+    /// Asserts every count is within 10% of `expected`.
+    fn assert_even(counts: &[usize], expected: usize) {
+        let tolerance = expected / 10;
+        for (value, &count) in counts.iter().enumerate() {
+            assert!(
+                count.abs_diff(expected) <= tolerance,
+                "value {value} drawn {count} times, expected about {expected}"
+            );
+        }
+    }
+
+    fn assert_distinct_in_range(picked: &[usize], n: usize) {
+        let unique: BTreeSet<usize> = picked.iter().copied().collect();
+        assert_eq!(unique.len(), picked.len());
+        assert!(picked.iter().all(|&index| index < n));
+    }
+
+    #[test]
+    fn derive_is_deterministic_and_index_dependent() {
+        let seed = U256::from(42);
+        assert_eq!(derive(seed, U256::ZERO), derive(seed, U256::ZERO));
+        assert_ne!(derive(seed, U256::ZERO), derive(seed, U256::ONE));
+        assert_eq!(
+            expand(seed, 3),
+            vec![
+                derive(seed, U256::ZERO),
+                derive(seed, U256::from(1)),
+                derive(seed, U256::from(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn uniform_stays_in_range() {
+        for i in 0..1_000 {
+            let word = derive(U256::from(i), U256::ZERO);
+            let value = uniform(word, U256::from(10), U256::from(20));
+            assert!(value >= U256::from(10) && value <= U256::from(20));
+        }
+        let word = derive(U256::ONE, U256::ZERO);
+        assert_eq!(uniform(word, U256::from(7), U256::from(7)), U256::from(7));
+        assert_eq!(uniform(word, U256::ZERO, U256::MAX), word);
+    }
+
+    #[test]
+    #[should_panic = "empty range"]
+    fn uniform_panics_on_empty_range() {
+        uniform(U256::ZERO, U256::from(2), U256::ONE);
+    }
+
+    #[test]
+    fn uniform_is_unbiased() {
+        let mut counts = [0usize; 10];
+        for i in 0..TRIALS {
+            let word = derive(U256::from(i), U256::ZERO);
+            counts[uniform(word, U256::ZERO, U256::from(9)).to::<usize>()] += 1;
+        }
+        assert_even(&counts, TRIALS / 10);
+    }
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        let mut items: Vec<usize> = (0..20).collect();
+        shuffle(U256::from(7), &mut items);
+        let mut sorted = items.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+
+        let mut empty: [usize; 0] = [];
+        shuffle(U256::from(7), &mut empty);
+        let mut single = [5];
+        shuffle(U256::from(7), &mut single);
+        assert_eq!(single, [5]);
+    }
+
+    #[test]
+    fn shuffle_is_unbiased() {
+        // Position of item 0 after shuffling `[0, 1, 2, 3]`.
+        let mut counts = [0usize; 4];
+        for i in 0..TRIALS {
+            let mut items = [0, 1, 2, 3];
+            shuffle(U256::from(i), &mut items);
+            let position = items.iter().position(|&item| item == 0).unwrap();
+            counts[position] += 1;
+        }
+        assert_even(&counts, TRIALS / 4);
+    }
+
+    #[test]
+    fn sample_indices_k_below_n() {
+        for i in 0..100 {
+            let picked = sample_indices(U256::from(i), 50, 10);
+            assert_eq!(picked.len(), 10);
+            assert_distinct_in_range(&picked, 50);
+        }
+    }
+
+    #[test]
+    fn sample_indices_k_equals_n() {
+        for i in 0..100 {
+            let mut picked = sample_indices(U256::from(i), 8, 8);
+            assert_distinct_in_range(&picked, 8);
+            picked.sort_unstable();
+            assert_eq!(picked, (0..8).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn sample_indices_edge_sizes() {
+        assert!(sample_indices(U256::ONE, 10, 0).is_empty());
+        assert!(sample_indices(U256::ONE, 0, 0).is_empty());
+        assert_eq!(sample_indices(U256::ONE, 1, 1), vec![0]);
+    }
+
+    #[test]
+    #[should_panic = "sample larger than population"]
+    fn sample_indices_panics_if_k_exceeds_n() {
+        sample_indices(U256::ONE, 3, 4);
+    }
+
+    #[test]
+    fn sample_indices_is_unbiased() {
+        // Every index of `0..5` is picked in 2 of 5 samples of size 2, and
+        // is the first pick in 1 of 5.
+        let mut picked_counts = [0usize; 5];
+        let mut first_counts = [0usize; 5];
+        for i in 0..TRIALS {
+            let picked = sample_indices(U256::from(i), 5, 2);
+            first_counts[picked[0]] += 1;
+            for index in picked {
+                picked_counts[index] += 1;
+            }
+        }
+        assert_even(&picked_counts, TRIALS * 2 / 5);
+        assert_even(&first_counts, TRIALS / 5);
+    }
+}
@@ -0,0 +1,250 @@
+//! Packed bookkeeping for randomness requests.
+//!
+//! The bookkeeping the fulfillment path needs lives in two storage words, so
+//! a callback costs two reads and two clears however many fields are
+//! tracked. The Supra consumer keeps the v2 client seed in a third slot:
+//! closing a request also reads it, and clears it only for requests that
+//! carry one.
+//!
+//! | word | bits       | field           |
+//! |------|------------|-----------------|
+//! | 0    | `0..160`   | `requester`     |
+//! | 0    | `160..192` | `timestamp`     |
+//! | 0    | `192..200` | `kind`          |
+//! | 0    | `200..208` | `status`        |
+//! | 1    | `0..64`    | `amount_bucket` |
+use stylus_sdk::{
+    alloy_primitives::{Address, U256, U64},
+    prelude::*,
+    storage::{StorageU256, StorageU64},
+};
+
+/// Lifecycle state of a randomness request.
+///
+/// A request starts as [`RequestStatus::Requested`] and moves to exactly one
+/// terminal state. Every change goes through [`RequestStatus::transition`].
+///
+/// Terminal states are never stored: closing a request clears its slot, so
+/// a fulfilled, cancelled, expired or superseded request reads back as
+/// [`RequestStatus::None`]. They only surface in events and return values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RequestStatus {
+    /// No request is stored under the nonce.
+    #[default]
+    None = 0,
+    /// Randomness was requested and not yet delivered.
+    Requested = 1,
+    /// Randomness was delivered and consumed.
+    Fulfilled = 2,
+    /// The consumer gave up on the request before delivery.
+    Cancelled = 3,
+    /// The request was not fulfilled in time.
+    Expired = 4,
+    /// A newer request replaced this one.
+    Superseded = 5,
+}
+
+impl RequestStatus {
+    /// Decodes a stored status byte. Unknown values decode to
+    /// [`RequestStatus::None`].
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Requested,
+            2 => Self::Fulfilled,
+            3 => Self::Cancelled,
+            4 => Self::Expired,
+            5 => Self::Superseded,
+            _ => Self::None,
+        }
+    }
+
+    /// Returns `next` if a request may move from `self` to it, `None`
+    /// otherwise.
+    pub fn transition(self, next: Self) -> Option<Self> {
+        let allowed = matches!(
+            (self, next),
+            (Self::None, Self::Requested)
+                | (
+                    Self::Requested,
+                    Self::Fulfilled | Self::Cancelled | Self::Expired | Self::Superseded
+                )
+        );
+        allowed.then_some(next)
+    }
+
+    /// Returns whether no further transition is possible from `self`.
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            Self::Fulfilled | Self::Cancelled | Self::Expired | Self::Superseded
+        )
+    }
+}
+
+/// Decoded bookkeeping of a single randomness request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RequestData {
+    /// Account the request was made for.
+    pub requester: Address,
+    /// Block timestamp at request time, in seconds.
+    pub timestamp: u32,
+    /// Consumer-defined request type.
+    pub kind: u8,
+    /// Lifecycle state.
+    pub status: RequestStatus,
+    /// Consumer-defined amount, typically scaled down to fit 64 bits.
+    pub amount_bucket: u64,
+}
+
+impl RequestData {
+    /// Packs every field but `amount_bucket` into the first storage word.
+    pub fn encode_head(&self) -> U256 {
+        let mut word = [0u8; 32];
+        word[6] = self.status as u8;
+        word[7] = self.kind;
+        word[8..12].copy_from_slice(&self.timestamp.to_be_bytes());
+        word[12..].copy_from_slice(self.requester.as_slice());
+        U256::from_be_bytes(word)
+    }
+
+    /// Rebuilds a request from its first storage word and `amount_bucket`.
+    pub fn decode(head: U256, amount_bucket: u64) -> Self {
+        let word = head.to_be_bytes::<32>();
+        Self {
+            requester: Address::from_slice(&word[12..]),
+            timestamp: u32::from_be_bytes([word[8], word[9], word[10], word[11]]),
+            kind: word[7],
+            status: RequestStatus::from_u8(word[6]),
+            amount_bucket,
+        }
+    }
+}
+
+/// Storage slot pair holding an encoded [`RequestData`].
+#[storage]
+pub struct StorageRequest {
+    head: StorageU256,
+    amount_bucket: StorageU64,
+}
+
+impl StorageRequest {
+    /// Reads and decodes the stored request.
+    pub fn load(&self) -> RequestData {
+        RequestData::decode(self.head.get(), self.amount_bucket.get().to::<u64>())
+    }
+
+    /// Encodes and writes `data`.
+    pub fn store(&mut self, data: RequestData) {
+        self.head.set(data.encode_head());
+        self.amount_bucket.set(U64::from(data.amount_bucket));
+    }
+
+    /// Returns the stored status without decoding the rest of the request.
+    pub fn status(&self) -> RequestStatus {
+        RequestStatus::from_u8(self.head.get().to_be_bytes::<32>()[6])
+    }
+
+    /// Zeroes both words, refunding their storage.
+    pub fn clear(&mut self) {
+        self.head.set(U256::ZERO);
+        self.amount_bucket.set(U64::ZERO);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use stylus_sdk::alloy_primitives::{address, U256};
+
+    use super::*;
+
+    const ALL: [RequestStatus; 6] = [
+        RequestStatus::None,
+        RequestStatus::Requested,
+        RequestStatus::Fulfilled,
+        RequestStatus::Cancelled,
+        RequestStatus::Expired,
+        RequestStatus::Superseded,
+    ];
+
+    #[test]
+    fn transitions() {
+        for from in ALL {
+            for to in ALL {
+                let allowed = match from {
+                    RequestStatus::None => to == RequestStatus::Requested,
+                    RequestStatus::Requested => to.is_terminal(),
+                    _ => false,
+                };
+                let expected = allowed.then_some(to);
+                assert_eq!(from.transition(to), expected, "{from:?} -> {to:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn status_round_trips_through_u8() {
+        for status in ALL {
+            assert_eq!(RequestStatus::from_u8(status as u8), status);
+        }
+        assert_eq!(RequestStatus::from_u8(6), RequestStatus::None);
+        assert_eq!(RequestStatus::from_u8(u8::MAX), RequestStatus::None);
+    }
+
+    #[test]
+    fn terminal_states() {
+        let terminal: Vec<_> = ALL.into_iter().filter(|s| s.is_terminal()).collect();
+        assert_eq!(
+            terminal,
+            [
+                RequestStatus::Fulfilled,
+                RequestStatus::Cancelled,
+                RequestStatus::Expired,
+                RequestStatus::Superseded,
+            ]
+        );
+    }
+
+    #[test]
+    fn request_round_trips_through_encoding() {
+        let requests = [
+            RequestData::default(),
+            RequestData {
+                requester: address!("ffffffffffffffffffffffffffffffffffffffff"),
+                timestamp: u32::MAX,
+                kind: u8::MAX,
+                status: RequestStatus::Superseded,
+                amount_bucket: u64::MAX,
+            },
+            RequestData {
+                requester: address!("00000000000000000000000000000000000a1ce0"),
+                timestamp: 1_700_000_000,
+                kind: 3,
+                status: RequestStatus::Requested,
+                amount_bucket: 42,
+            },
+        ];
+        for request in requests {
+            let head = request.encode_head();
+            assert_eq!(RequestData::decode(head, request.amount_bucket), request);
+        }
+    }
+
+    #[test]
+    fn encoding_layout() {
+        let request = RequestData {
+            requester: address!("00000000000000000000000000000000000a1ce0"),
+            timestamp: 1,
+            kind: 2,
+            status: RequestStatus::Requested,
+            amount_bucket: 0,
+        };
+        let expected = U256::from(0xa1ce0u64)
+            | (U256::from(1) << 160)
+            | (U256::from(2) << 192)
+            | (U256::from(1) << 200);
+        assert_eq!(request.encode_head(), expected);
+    }
+}
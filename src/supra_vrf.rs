@@ -0,0 +1,1334 @@
+//! Reusable consumer plumbing for Supra dVRF.
+//!
+//! Embed [`SupraVrfConsumer`] in a contract's storage to request random
+//! words from a Supra router and to validate the router's callback:
+//!
+//! ```rust,ignore
+//! #[entrypoint]
+//! #[storage]
+//! struct MyContract {
+//!     vrf: SupraVrfConsumer,
+//! }
+//!
+//! #[public]
+//! impl MyContract {
+//!     #[constructor]
+//!     pub fn constructor(&mut self, router: Address, client_wallet: Address) -> Result<(), supra_vrf::Error> {
+//!         self.vrf.constructor(router, client_wallet)?;
+//!         self.vrf._register_callback(ROLL, "onRandomness(uint256,uint256[])");
+//!         Ok(())
+//!     }
+//!
+//!     #[payable]
+//!     pub fn roll(&mut self) -> Result<U256, supra_vrf::Error> {
+//!         let requester = self.vm().msg_sender();
+//!         let paid = self.vm().msg_value();
+//!         self.vrf._request_randomness(ROLL, 1, U256::ONE, requester, 0, U256::ZERO, paid)
+//!     }
+//!
+//!     #[selector(name = "onRandomness")]
+//!     pub fn on_randomness(&mut self, nonce: U256, rng_list: Vec<U256>) -> Result<(), supra_vrf::Error> {
+//!         let request = self.vrf._fulfill(nonce, ROLL)?;
+//!         // consume `rng_list` on behalf of `request.requester`...
+//!         Ok(())
+//!     }
+//! }
+//! ```
+//!
+//! Contracts expose the consumer's state to integrators by implementing
+//! [`ISupraVrfConsumer`] over the embedded state and listing it in
+//! `#[implements]`.
+//!
+//! `ROLL` is a consumer-defined request kind. Each kind registers the
+//! Solidity signature of the callback its randomness is delivered to.
+//! Contracts with several request types sharing the router's nonce space
+//! give each callback its own kind, so a nonce requested for one callback is
+//! rejected by the others.
+//!
+//! The calling contract must be whitelisted under the configured client
+//! wallet in Supra's deposit contract for requests to succeed. Requests are
+//! normally prepaid: the client wallet tops up its balance with
+//! `depositFundClient()` directly and no fee is configured here.
+//!
+//! A per-request fee can instead be forwarded from each request. Supra's
+//! deposit contract credits `depositFundClient()` to `msg.sender`, which is
+//! then the consumer contract itself, so this mode requires the contract to
+//! be registered at Supra as its own client: `client_wallet` must be the
+//! contract's address, and the contract must be added to its own whitelist.
+//! [`SupraVrfConsumer::_set_request_fee`] enforces the address match. The
+//! requesting entrypoint must be `#[payable]` and pass the value it sets
+//! aside for the request as `paid`; the fee is forwarded and anything above
+//! it is refunded to the caller.
+//!
+//! With the `dev-vrf` feature no router is involved: requests get local
+//! nonces, and [`dev_fulfill`] delivers words derived from block data and
+//! the nonce to the registered callback within the same transaction. The
+//! callback is dispatched in-process through the contract's router, as
+//! multicall does, so no reentrant call is made. The requesting entrypoint
+//! calls it right after the request:
+//!
+//! ```rust,ignore
+//! let nonce = self.vrf._request_randomness(ROLL, 1, U256::ONE, requester, 0, U256::ZERO, paid)?;
+//! #[cfg(feature = "dev-vrf")]
+//! supra_vrf::dev_fulfill(self, |s| &mut s.vrf, <Self as Router<Self>>::route, nonce, 1)?;
+//! ```
+//!
+//! The words are predictable, so the feature is for local devnets only.
+use alloc::string::String;
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256, U8},
+    alloy_sol_types::sol,
+    call::Call,
+    prelude::*,
+    storage::{StorageAddress, StorageBool, StorageMap, StorageString, StorageU256, StorageU8},
+};
+
+#[cfg(feature = "dev-vrf")]
+use stylus_sdk::{alloy_sol_types::SolValue, crypto::keccak, ArbResult};
+
+use crate::{
+    rand_utils,
+    request::{RequestData, RequestStatus, StorageRequest},
+};
+
+sol_interface! {
+    interface ISupraRouter {
+        function generateRequest(string memory function_sig, uint8 rng_count, uint256 num_confirmations, address client_wallet_address) external returns (uint256);
+    }
+
+    interface ISupraRouterV2 {
+        function generateRequest(string memory function_sig, uint8 rng_count, uint256 num_confirmations, uint256 client_seed, address client_wallet_address) external returns (uint256);
+    }
+
+    interface ISupraDeposit {
+        function depositFundClient() external payable;
+    }
+}
+
+/// Router generation whose `generateRequest` takes no client seed.
+pub const ROUTER_V1: u8 = 1;
+
+/// Router generation whose `generateRequest` takes a client seed.
+pub const ROUTER_V2: u8 = 2;
+
+sol! {
+    /// Emitted when randomness is requested from the Supra router.
+    #[allow(missing_docs)]
+    event RandomnessRequested(uint256 indexed nonce, uint8 rngCount, uint256 clientSeed);
+
+    /// Emitted when the Supra router delivers randomness for `nonce`.
+    #[allow(missing_docs)]
+    event RandomnessFulfilled(uint256 indexed nonce);
+
+    /// Emitted when a request is cancelled, expires or is superseded.
+    #[allow(missing_docs)]
+    event RequestClosed(uint256 indexed nonce, uint8 status);
+
+    /// Emitted when the per-request fee or its deposit contract changes.
+    #[allow(missing_docs)]
+    event RequestFeeUpdated(address indexed deposit, uint256 fee);
+
+    /// Emitted when `fulfiller` is added to or removed from the accounts
+    /// allowed to deliver randomness besides the router.
+    #[allow(missing_docs)]
+    event FulfillerUpdated(address indexed fulfiller, bool allowed);
+
+    /// Emitted when the router interface generation changes.
+    #[allow(missing_docs)]
+    event RouterVersionUpdated(uint8 version);
+
+    /// Emitted when request `kind` is bound to the callback `signature`.
+    #[allow(missing_docs)]
+    event CallbackRegistered(uint8 indexed kind, string signature);
+}
+
+sol! {
+    /// The router address is invalid (e.g. `Address::ZERO`).
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfInvalidRouter(address router);
+
+    /// `version` is not a known router interface generation.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfUnsupportedRouterVersion(uint8 version);
+
+    /// The callback was invoked by an account that is neither the router nor
+    /// an allowed fulfiller.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfUnauthorizedFulfiller(address fulfiller);
+
+    /// The callback refers to a nonce that is not pending.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfUnknownRequest(uint256 nonce);
+
+    /// The request under `nonce` cannot move from status `from` to `to`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfInvalidTransition(uint256 nonce, uint8 from, uint8 to);
+
+    /// The request under `nonce` was made for another callback.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfKindMismatch(uint256 nonce, uint8 expected, uint8 actual);
+
+    /// No callback signature is registered for request `kind`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfUnregisteredKind(uint8 kind);
+
+    /// The call to the router's `generateRequest` reverted.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfRequestFailed();
+
+    /// A non-zero fee cannot be forwarded to `deposit`, which has no code.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfInvalidFeeDeposit(address deposit);
+
+    /// Forwarded fees are credited to the contract, but requests are billed
+    /// to `clientWallet`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfFeeClientMismatch(address clientWallet);
+
+    /// The value sent with a request does not cover the request `fee`.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfInsufficientFee(uint256 paid, uint256 fee);
+
+    /// The `paid` budget of a request is more than the `value` sent.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfFeeExceedsValue(uint256 paid, uint256 value);
+
+    /// Forwarding the fee or refunding the excess value failed.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    error SupraVrfFeeTransferFailed();
+}
+
+/// An error that occurred in the [`SupraVrfConsumer`] plumbing.
+#[derive(SolidityError, Debug)]
+pub enum Error {
+    /// The router address is invalid (e.g. `Address::ZERO`).
+    InvalidRouter(SupraVrfInvalidRouter),
+    /// The router interface generation is unknown.
+    UnsupportedRouterVersion(SupraVrfUnsupportedRouterVersion),
+    /// The callback was invoked by an account that may not fulfill.
+    UnauthorizedFulfiller(SupraVrfUnauthorizedFulfiller),
+    /// The callback refers to a nonce that is not pending.
+    UnknownRequest(SupraVrfUnknownRequest),
+    /// The request cannot move to the requested status.
+    InvalidTransition(SupraVrfInvalidTransition),
+    /// The request was made for another callback.
+    KindMismatch(SupraVrfKindMismatch),
+    /// No callback signature is registered for the request kind.
+    UnregisteredKind(SupraVrfUnregisteredKind),
+    /// The call to the router's `generateRequest` reverted.
+    RequestFailed(SupraVrfRequestFailed),
+    /// The fee deposit contract is invalid.
+    InvalidFeeDeposit(SupraVrfInvalidFeeDeposit),
+    /// Forwarded fees would not be credited to the client wallet.
+    FeeClientMismatch(SupraVrfFeeClientMismatch),
+    /// The value sent with a request does not cover the request fee.
+    InsufficientFee(SupraVrfInsufficientFee),
+    /// The fee budget of a request exceeds the value sent with the call.
+    FeeExceedsValue(SupraVrfFeeExceedsValue),
+    /// Forwarding the fee or refunding the excess value failed.
+    FeeTransferFailed(SupraVrfFeeTransferFailed),
+}
+
+/// State of a Supra dVRF consumer.
+#[storage]
+pub struct SupraVrfConsumer {
+    /// Supra router allowed to request and fulfill randomness.
+    pub(crate) router: StorageAddress,
+    /// Wallet the consumer contract is whitelisted under at Supra.
+    pub(crate) client_wallet: StorageAddress,
+    /// Bookkeeping of requests not yet fulfilled, by nonce.
+    pub(crate) requests: StorageMap<U256, StorageRequest>,
+    /// Native fee charged per request, zero if requests are prepaid.
+    pub(crate) request_fee: StorageU256,
+    /// Supra deposit contract the per-request fee is forwarded to.
+    pub(crate) fee_deposit: StorageAddress,
+    /// Accounts besides the router allowed to deliver randomness.
+    pub(crate) fulfillers: StorageMap<Address, StorageBool>,
+    /// Router interface generation, [`ROUTER_V1`] when unset.
+    pub(crate) router_version: StorageU8,
+    /// Client seed sent with each open request, zero for [`ROUTER_V1`].
+    pub(crate) client_seeds: StorageMap<U256, StorageU256>,
+    /// Solidity signature of the callback serving each request kind.
+    pub(crate) callbacks: StorageMap<U8, StorageString>,
+    /// Last nonce handed out locally, only used with the `dev-vrf` feature.
+    pub(crate) dev_nonce: StorageU256,
+    /// Set while [`dev_fulfill`] delivers a callback, only used with the
+    /// `dev-vrf` feature.
+    pub(crate) dev_delivering: StorageBool,
+}
+
+/// NOTE: Implementation of [`TopLevelStorage`] to be able to call the router
+/// with `&mut self`.
+unsafe impl TopLevelStorage for SupraVrfConsumer {}
+
+/// Read-only surface a Supra dVRF consumer exposes to integrators.
+pub trait ISupraVrfConsumer {
+    /// Returns the Supra router address.
+    fn supra_router(&self) -> Address;
+
+    /// Returns the client wallet the contract is whitelisted under.
+    fn supra_client_wallet(&self) -> Address;
+
+    /// Returns whether `nonce` was requested and is awaiting fulfillment.
+    fn is_request_pending(&self, nonce: U256) -> bool;
+
+    /// Returns whether `account` may deliver randomness to the contract.
+    fn is_supra_fulfiller(&self, account: Address) -> bool;
+}
+
+impl SupraVrfConsumer {
+    /// Initializes the consumer with the Supra `router` and `client_wallet`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidRouter`] - If `router` is `Address::ZERO`.
+    pub fn constructor(&mut self, router: Address, client_wallet: Address) -> Result<(), Error> {
+        if router.is_zero() {
+            return Err(Error::InvalidRouter(SupraVrfInvalidRouter { router }));
+        }
+        self.router.set(router);
+        self.client_wallet.set(client_wallet);
+        Ok(())
+    }
+
+    /// Returns the Supra router address.
+    pub fn router(&self) -> Address {
+        self.router.get()
+    }
+
+    /// Returns the router interface generation requests are encoded for.
+    pub fn router_version(&self) -> u8 {
+        match self.router_version.get().to::<u8>() {
+            0 => ROUTER_V1,
+            version => version,
+        }
+    }
+
+    /// Selects the router interface generation, so the consumer can be
+    /// pointed at either deployment without being redeployed. Access
+    /// control is up to the embedding contract.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::UnsupportedRouterVersion`] - If `version` is neither
+    ///   [`ROUTER_V1`] nor [`ROUTER_V2`].
+    pub fn _set_router_version(&mut self, version: u8) -> Result<(), Error> {
+        if version != ROUTER_V1 && version != ROUTER_V2 {
+            return Err(Error::UnsupportedRouterVersion(
+                SupraVrfUnsupportedRouterVersion { version },
+            ));
+        }
+        self.router_version.set(U8::from(version));
+        self.vm().log(RouterVersionUpdated { version });
+        Ok(())
+    }
+
+    /// Returns whether `account` may deliver randomness: the router always
+    /// can, other accounts only once allowed by [`Self::_set_fulfiller`].
+    /// With the `dev-vrf` feature any callback delivered by [`dev_fulfill`]
+    /// is accepted too.
+    pub fn is_fulfiller(&self, account: Address) -> bool {
+        account == self.router.get()
+            || self.fulfillers.get(account)
+            || (cfg!(feature = "dev-vrf") && self.dev_delivering.get())
+    }
+
+    /// Allows or disallows `fulfiller` to deliver randomness besides the
+    /// router, for router versions that call back from another address.
+    /// Access control is up to the embedding contract.
+    pub fn _set_fulfiller(&mut self, fulfiller: Address, allowed: bool) {
+        self.fulfillers.setter(fulfiller).set(allowed);
+        self.vm().log(FulfillerUpdated { fulfiller, allowed });
+    }
+
+    /// Returns the client wallet the contract is whitelisted under.
+    pub fn client_wallet(&self) -> Address {
+        self.client_wallet.get()
+    }
+
+    /// Returns the native fee charged per request.
+    pub fn request_fee(&self) -> U256 {
+        self.request_fee.get()
+    }
+
+    /// Returns the deposit contract the per-request fee is forwarded to.
+    pub fn fee_deposit(&self) -> Address {
+        self.fee_deposit.get()
+    }
+
+    /// Charges `fee` per request and forwards it to `deposit`. A zero `fee`
+    /// disables charging. Access control is up to the embedding contract.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::InvalidFeeDeposit`] - If `fee` is non-zero and `deposit`
+    ///   has no code, since value sent there would be lost.
+    /// * [`Error::FeeClientMismatch`] - If `fee` is non-zero and the client
+    ///   wallet is not the contract itself, see the module docs.
+    pub fn _set_request_fee(&mut self, deposit: Address, fee: U256) -> Result<(), Error> {
+        if !fee.is_zero() {
+            if deposit.is_zero() || self.vm().code_size(deposit) == 0 {
+                return Err(Error::InvalidFeeDeposit(SupraVrfInvalidFeeDeposit {
+                    deposit,
+                }));
+            }
+            let client_wallet = self.client_wallet.get();
+            if client_wallet != self.vm().contract_address() {
+                return Err(Error::FeeClientMismatch(SupraVrfFeeClientMismatch {
+                    clientWallet: client_wallet,
+                }));
+            }
+        }
+        self.fee_deposit.set(deposit);
+        self.request_fee.set(fee);
+        self.vm().log(RequestFeeUpdated { deposit, fee });
+        Ok(())
+    }
+
+    /// Returns whether `nonce` was requested and is awaiting fulfillment.
+    pub fn is_pending(&self, nonce: U256) -> bool {
+        self.requests.get(nonce).status() == RequestStatus::Requested
+    }
+
+    /// Returns the callback signature registered for request `kind`, empty
+    /// if none.
+    pub fn callback_signature(&self, kind: u8) -> String {
+        self.callbacks.get(U8::from(kind)).get_string()
+    }
+
+    /// Binds request `kind` to the public function with Solidity signature
+    /// `signature`, e.g. `"onRandomness(uint256,uint256[])"`. The router
+    /// calls it back with `(nonce, rng_list)`. Access control is up to the
+    /// embedding contract.
+    ///
+    /// The router delivers randomness with a low-level call and ignores its
+    /// result, so a signature that does not select the intended function
+    /// only shows up as requests that are never fulfilled. Consumers should
+    /// test that each registered signature routes to their callback, as
+    /// this module's tests do for a sample consumer.
+    pub fn _register_callback(&mut self, kind: u8, signature: &str) {
+        self.callbacks
+            .setter(U8::from(kind))
+            .set_str(String::from(signature));
+        self.vm().log(CallbackRegistered {
+            kind,
+            signature: String::from(signature),
+        });
+    }
+
+    /// Returns the client seed sent with the open request under `nonce`,
+    /// zero if none was sent.
+    pub fn client_seed(&self, nonce: U256) -> U256 {
+        self.client_seeds.get(nonce)
+    }
+
+    /// Returns the bookkeeping stored for `nonce`, zeroed if unknown.
+    pub fn request(&self, nonce: U256) -> RequestData {
+        self.requests.get(nonce).load()
+    }
+
+    /// Requests `rng_count` random words from the router, to be delivered to
+    /// the callback registered for `kind` once `num_confirmations` blocks
+    /// have passed. `requester`, `kind` and `amount_bucket` are stored with
+    /// the request and handed back by [`Self::_fulfill`].
+    ///
+    /// `paid` is the value the caller sets aside for this request out of
+    /// `msg_value`. If a fee is configured, it is taken from `paid` and the
+    /// rest is refunded to the caller; otherwise `paid` is left untouched.
+    /// An entrypoint making several requests must split `msg_value` between
+    /// them, e.g. pass [`Self::request_fee`] to each, as the value is not
+    /// tracked across requests. `paid` may never exceed `msg_value`, so a
+    /// refund cannot be paid out of the contract's own balance.
+    ///
+    /// With a [`ROUTER_V2`] router, `client_seed` is mixed into the delivered
+    /// randomness by Supra; a zero `client_seed` makes the contract derive
+    /// one from `requester` and the block number. The seed is kept with the
+    /// request and emitted in [`RandomnessRequested`] so the result can be
+    /// verified. [`ROUTER_V1`] routers take no seed.
+    ///
+    /// Returns the request nonce.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::UnregisteredKind`] - If no callback is registered for
+    ///   `kind`.
+    /// * [`Error::FeeExceedsValue`] - If a fee is configured and `paid` is
+    ///   more than `msg_value`.
+    /// * [`Error::InsufficientFee`] - If a fee is configured and `paid` does
+    ///   not cover it.
+    /// * [`Error::FeeTransferFailed`] - If forwarding the fee or refunding
+    ///   the excess fails.
+    /// * [`Error::RequestFailed`] - If the router call reverts.
+    /// * [`Error::InvalidTransition`] - If the router returns a nonce that
+    ///   is still in use.
+    #[allow(clippy::too_many_arguments)]
+    pub fn _request_randomness(
+        &mut self,
+        kind: u8,
+        rng_count: u8,
+        num_confirmations: U256,
+        requester: Address,
+        amount_bucket: u64,
+        client_seed: U256,
+        paid: U256,
+    ) -> Result<U256, Error> {
+        let callback_sig = self.callback_signature(kind);
+        if callback_sig.is_empty() {
+            return Err(Error::UnregisteredKind(SupraVrfUnregisteredKind { kind }));
+        }
+        self._collect_request_fee(paid)?;
+
+        let version = self.router_version();
+        let client_seed = match version {
+            ROUTER_V2 if client_seed.is_zero() => rand_utils::derive(
+                U256::from_be_slice(requester.as_slice()),
+                U256::from(self.vm().block_number()),
+            ),
+            ROUTER_V2 => client_seed,
+            _ => U256::ZERO,
+        };
+        let nonce =
+            self._generate_request(&callback_sig, rng_count, num_confirmations, client_seed)?;
+
+        let timestamp = self.vm().block_timestamp() as u32;
+        let mut slot = self.requests.setter(nonce);
+        let current = slot.status();
+        let Some(status) = current.transition(RequestStatus::Requested) else {
+            return Err(invalid_transition(nonce, current, RequestStatus::Requested));
+        };
+        slot.store(RequestData {
+            requester,
+            timestamp,
+            kind,
+            status,
+            amount_bucket,
+        });
+        if !client_seed.is_zero() {
+            self.client_seeds.setter(nonce).set(client_seed);
+        }
+        self.vm().log(RandomnessRequested {
+            nonce,
+            rngCount: rng_count,
+            clientSeed: client_seed,
+        });
+        Ok(nonce)
+    }
+
+    /// Validates a router callback for `nonce`, clears its bookkeeping and
+    /// returns it. Must be called first thing in the consumer's callback,
+    /// with the request `kind` that callback serves.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::UnauthorizedFulfiller`] - If the caller is neither the router
+    ///   nor an allowed fulfiller.
+    /// * [`Error::UnknownRequest`] - If no request is stored under `nonce`.
+    /// * [`Error::KindMismatch`] - If the request was made with another
+    ///   `kind`.
+    pub fn _fulfill(&mut self, nonce: U256, kind: u8) -> Result<RequestData, Error> {
+        let sender = self.vm().msg_sender();
+        if !self.is_fulfiller(sender) {
+            return Err(Error::UnauthorizedFulfiller(
+                SupraVrfUnauthorizedFulfiller { fulfiller: sender },
+            ));
+        }
+        let request = self.requests.get(nonce).load();
+        if request.status == RequestStatus::Requested && request.kind != kind {
+            return Err(Error::KindMismatch(SupraVrfKindMismatch {
+                nonce,
+                expected: kind,
+                actual: request.kind,
+            }));
+        }
+        self._close_loaded(nonce, request, RequestStatus::Fulfilled)
+    }
+
+    /// Moves the request under `nonce` to the terminal status `next` and
+    /// returns its bookkeeping. Terminal requests are not kept: their slots
+    /// are cleared, so a late callback for the nonce is rejected as
+    /// unknown. Use it to cancel, expire or supersede a request; access
+    /// control is up to the embedding contract.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::UnknownRequest`] - If no request is stored under `nonce`.
+    /// * [`Error::InvalidTransition`] - If `next` is not a terminal status.
+    pub fn _close(&mut self, nonce: U256, next: RequestStatus) -> Result<RequestData, Error> {
+        let request = self.requests.get(nonce).load();
+        self._close_loaded(nonce, request, next)
+    }
+
+    /// Closes the request under `nonce` as [`Self::_close`] does, given its
+    /// already loaded bookkeeping.
+    fn _close_loaded(
+        &mut self,
+        nonce: U256,
+        request: RequestData,
+        next: RequestStatus,
+    ) -> Result<RequestData, Error> {
+        if request.status == RequestStatus::None {
+            return Err(Error::UnknownRequest(SupraVrfUnknownRequest { nonce }));
+        }
+        match request.status.transition(next) {
+            Some(status) if status.is_terminal() => {}
+            _ => return Err(invalid_transition(nonce, request.status, next)),
+        }
+
+        self.requests.setter(nonce).clear();
+        if !self.client_seeds.get(nonce).is_zero() {
+            self.client_seeds.delete(nonce);
+        }
+        if next == RequestStatus::Fulfilled {
+            self.vm().log(RandomnessFulfilled { nonce });
+        } else {
+            self.vm().log(RequestClosed {
+                nonce,
+                status: next as u8,
+            });
+        }
+        Ok(request)
+    }
+
+    /// Asks the router for `rng_count` words delivered to `callback_sig`,
+    /// encoding the call for the configured router version.
+    #[cfg(not(feature = "dev-vrf"))]
+    fn _generate_request(
+        &mut self,
+        callback_sig: &str,
+        rng_count: u8,
+        num_confirmations: U256,
+        client_seed: U256,
+    ) -> Result<U256, Error> {
+        let router = self.router.get();
+        let client_wallet = self.client_wallet.get();
+        let version = self.router_version();
+        let call = Call::new_mutating(self);
+        if version == ROUTER_V2 {
+            ISupraRouterV2::new(router).generate_request(
+                self.vm(),
+                call,
+                String::from(callback_sig),
+                rng_count,
+                num_confirmations,
+                client_seed,
+                client_wallet,
+            )
+        } else {
+            ISupraRouter::new(router).generate_request(
+                self.vm(),
+                call,
+                String::from(callback_sig),
+                rng_count,
+                num_confirmations,
+                client_wallet,
+            )
+        }
+        .map_err(|_| Error::RequestFailed(SupraVrfRequestFailed {}))
+    }
+
+    /// Hands out the next local nonce instead of calling the router.
+    #[cfg(feature = "dev-vrf")]
+    fn _generate_request(
+        &mut self,
+        _callback_sig: &str,
+        _rng_count: u8,
+        _num_confirmations: U256,
+        _client_seed: U256,
+    ) -> Result<U256, Error> {
+        let nonce = self.dev_nonce.get() + U256::ONE;
+        self.dev_nonce.set(nonce);
+        Ok(nonce)
+    }
+
+    /// Forwards the configured fee out of `paid` to the deposit contract and
+    /// refunds the excess to the caller.
+    fn _collect_request_fee(&mut self, paid: U256) -> Result<(), Error> {
+        let fee = self.request_fee.get();
+        if fee.is_zero() {
+            return Ok(());
+        }
+
+        let value = self.vm().msg_value();
+        if paid > value {
+            return Err(Error::FeeExceedsValue(SupraVrfFeeExceedsValue {
+                paid,
+                value,
+            }));
+        }
+        if paid < fee {
+            return Err(Error::InsufficientFee(SupraVrfInsufficientFee {
+                paid,
+                fee,
+            }));
+        }
+
+        let deposit = ISupraDeposit::new(self.fee_deposit.get());
+        let call = Call::new_payable(self, fee);
+        deposit
+            .deposit_fund_client(self.vm(), call)
+            .map_err(|_| Error::FeeTransferFailed(SupraVrfFeeTransferFailed {}))?;
+
+        let excess = paid - fee;
+        if !excess.is_zero() {
+            let caller = self.vm().msg_sender();
+            self.vm()
+                .transfer_eth(caller, excess)
+                .map_err(|_| Error::FeeTransferFailed(SupraVrfFeeTransferFailed {}))?;
+        }
+        Ok(())
+    }
+}
+
+/// Delivers `rng_count` pseudo-random words for the pending request under
+/// `nonce` to its registered callback, standing in for the router.
+///
+/// `vrf` selects the consumer state inside `storage`, and `route` is the
+/// contract's `Router::route`, through which the callback is dispatched
+/// in-process.
+///
+/// # Errors
+///
+/// * [`Error::UnknownRequest`] - If no request is pending under `nonce`.
+/// * [`Error::RequestFailed`] - If the callback is not exported or reverts.
+#[cfg(feature = "dev-vrf")]
+pub fn dev_fulfill<S: TopLevelStorage>(
+    storage: &mut S,
+    vrf: fn(&mut S) -> &mut SupraVrfConsumer,
+    route: fn(&mut S, u32, &[u8]) -> Option<ArbResult>,
+    nonce: U256,
+    rng_count: u8,
+) -> Result<(), Error> {
+    let consumer = vrf(storage);
+    let request = consumer.request(nonce);
+    if request.status != RequestStatus::Requested {
+        return Err(Error::UnknownRequest(SupraVrfUnknownRequest { nonce }));
+    }
+    let block_data = (
+        U256::from(consumer.vm().block_number()),
+        U256::from(consumer.vm().block_timestamp()),
+        nonce,
+        consumer.client_seed(nonce),
+    );
+    let seed = U256::from_be_bytes(keccak(block_data.abi_encode()).0);
+    let rng_list = rand_utils::expand(seed, rng_count as usize);
+    let callback_sig = consumer.callback_signature(request.kind);
+    let selector = u32::from_be_bytes(keccak(callback_sig.as_bytes())[..4].try_into().unwrap());
+    let input = (nonce, rng_list).abi_encode_params();
+
+    consumer.dev_delivering.set(true);
+    let result = route(storage, selector, &input);
+    vrf(storage).dev_delivering.set(false);
+    match result {
+        Some(Ok(_)) => Ok(()),
+        _ => Err(Error::RequestFailed(SupraVrfRequestFailed {})),
+    }
+}
+
+fn invalid_transition(nonce: U256, from: RequestStatus, to: RequestStatus) -> Error {
+    Error::InvalidTransition(SupraVrfInvalidTransition {
+        nonce,
+        from: from as u8,
+        to: to as u8,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use stylus_sdk::{
+        abi::Router,
+        alloy_primitives::{address, Address, U256},
+        alloy_sol_types::{SolEvent, SolValue},
+        crypto::keccak,
+        prelude::*,
+        storage::StorageU256,
+        testing::*,
+        ArbResult,
+    };
+
+    use super::*;
+
+    const ROUTER: Address = address!("00000000000000000000000000000000000000f1");
+    const CLIENT_WALLET: Address = address!("00000000000000000000000000000000000000c1");
+    const REQUESTER: Address = address!("00000000000000000000000000000000000a1ce0");
+
+    const ROLL: u8 = 1;
+    const DRAW: u8 = 2;
+
+    /// Callback signature [`TestConsumer`] registers for each request kind.
+    const CALLBACKS: [(u8, &str); 2] = [
+        (ROLL, "onRoll(uint256,uint256[])"),
+        (DRAW, "onDraw(uint256,uint256[])"),
+    ];
+
+    /// Minimal consumer embedding [`SupraVrfConsumer`] the way a contract
+    /// would, with one callback per request kind.
+    #[storage]
+    struct TestConsumer {
+        vrf: SupraVrfConsumer,
+        last_word: StorageU256,
+    }
+
+    unsafe impl TopLevelStorage for TestConsumer {}
+
+    #[public]
+    #[implements(ISupraVrfConsumer)]
+    impl TestConsumer {
+        #[selector(name = "onRoll")]
+        pub fn on_roll(&mut self, nonce: U256, rng_list: Vec<U256>) -> Result<(), Error> {
+            self.vrf._fulfill(nonce, ROLL)?;
+            self.last_word.set(rng_list[0]);
+            Ok(())
+        }
+
+        #[selector(name = "onDraw")]
+        pub fn on_draw(&mut self, nonce: U256, rng_list: Vec<U256>) -> Result<(), Error> {
+            self.vrf._fulfill(nonce, DRAW)?;
+            self.last_word.set(rng_list[0]);
+            Ok(())
+        }
+    }
+
+    #[public]
+    impl ISupraVrfConsumer for TestConsumer {
+        fn supra_router(&self) -> Address {
+            self.vrf.router()
+        }
+
+        fn supra_client_wallet(&self) -> Address {
+            self.vrf.client_wallet()
+        }
+
+        fn is_request_pending(&self, nonce: U256) -> bool {
+            self.vrf.is_pending(nonce)
+        }
+
+        fn is_supra_fulfiller(&self, account: Address) -> bool {
+            self.vrf.is_fulfiller(account)
+        }
+    }
+
+    impl TestConsumer {
+        fn deploy(vm: &TestVM) -> Self {
+            let mut consumer = Self::from(vm);
+            consumer.vrf.constructor(ROUTER, CLIENT_WALLET).unwrap();
+            for (kind, signature) in CALLBACKS {
+                consumer.vrf._register_callback(kind, signature);
+            }
+            consumer
+        }
+
+        /// Stores a pending request as `_request_randomness` does once the
+        /// router returned `nonce`.
+        fn open(&mut self, nonce: U256, kind: u8) {
+            self.vrf.requests.setter(nonce).store(RequestData {
+                requester: REQUESTER,
+                timestamp: 0,
+                kind,
+                status: RequestStatus::Requested,
+                amount_bucket: 0,
+            });
+        }
+
+        /// Delivers `word` for `nonce` the way the router does: a call whose
+        /// selector is derived from `signature`, dispatched by the contract's
+        /// router.
+        fn deliver(&mut self, signature: &str, nonce: U256, word: U256) -> Option<ArbResult> {
+            let selector =
+                u32::from_be_bytes(keccak(signature.as_bytes())[..4].try_into().unwrap());
+            let input = (nonce, vec![word]).abi_encode_params();
+            <Self as Router<Self>>::route(self, selector, &input)
+        }
+    }
+
+    /// Makes the mocked router answer the next request for `kind` with
+    /// `nonce`.
+    #[cfg(not(feature = "dev-vrf"))]
+    fn mock_router(vm: &TestVM, kind: u8, nonce: U256, client_seed: Option<U256>) {
+        let (_, signature) = CALLBACKS.iter().find(|(k, _)| *k == kind).unwrap();
+        let signature = alloc::string::String::from(*signature);
+        let (selector, params) = match client_seed {
+            Some(seed) => (
+                "generateRequest(string,uint8,uint256,uint256,address)",
+                (signature, 1u8, U256::ONE, seed, CLIENT_WALLET).abi_encode_params(),
+            ),
+            None => (
+                "generateRequest(string,uint8,uint256,address)",
+                (signature, 1u8, U256::ONE, CLIENT_WALLET).abi_encode_params(),
+            ),
+        };
+        let mut calldata = keccak(selector.as_bytes())[..4].to_vec();
+        calldata.extend(params);
+        vm.mock_call(ROUTER, calldata, Ok(nonce.abi_encode()));
+    }
+
+    fn request(consumer: &mut TestConsumer, kind: u8, client_seed: U256) -> Result<U256, Error> {
+        consumer
+            .vrf
+            ._request_randomness(kind, 1, U256::ONE, REQUESTER, 7, client_seed, U256::ZERO)
+    }
+
+    #[test]
+    fn constructor_validates_router() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::from(&vm);
+        let err = consumer
+            .vrf
+            .constructor(Address::ZERO, CLIENT_WALLET)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidRouter(_)));
+
+        let consumer = TestConsumer::deploy(&vm);
+        assert_eq!(consumer.vrf.router(), ROUTER);
+        assert_eq!(consumer.vrf.client_wallet(), CLIENT_WALLET);
+        assert_eq!(consumer.vrf.router_version(), ROUTER_V1);
+        assert!(consumer.vrf.is_fulfiller(ROUTER));
+        assert!(!consumer.vrf.is_fulfiller(REQUESTER));
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-vrf"))]
+    fn request_stores_bookkeeping() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        vm.set_block_timestamp(1_000);
+        mock_router(&vm, ROLL, U256::from(5), None);
+
+        let nonce = request(&mut consumer, ROLL, U256::ZERO).unwrap();
+
+        assert_eq!(nonce, U256::from(5));
+        assert!(consumer.vrf.is_pending(nonce));
+        assert_eq!(
+            consumer.vrf.request(nonce),
+            RequestData {
+                requester: REQUESTER,
+                timestamp: 1_000,
+                kind: ROLL,
+                status: RequestStatus::Requested,
+                amount_bucket: 7,
+            }
+        );
+        assert_eq!(consumer.vrf.client_seed(nonce), U256::ZERO);
+
+        let logs = vm.get_emitted_logs();
+        let (topics, _) = logs.last().unwrap();
+        assert_eq!(topics[0], RandomnessRequested::SIGNATURE_HASH);
+    }
+
+    #[test]
+    fn request_rejects_unregistered_kind() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+
+        let err = request(&mut consumer, 9, U256::ZERO).unwrap_err();
+        assert!(matches!(err, Error::UnregisteredKind(_)));
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-vrf"))]
+    fn request_rejects_reused_nonce() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        consumer.open(U256::from(5), ROLL);
+        mock_router(&vm, ROLL, U256::from(5), None);
+
+        let err = request(&mut consumer, ROLL, U256::ZERO).unwrap_err();
+        assert!(matches!(err, Error::InvalidTransition(_)));
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-vrf"))]
+    fn v2_request_keeps_client_seed_until_closed() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        consumer.vrf._set_router_version(ROUTER_V2).unwrap();
+        let seed = U256::from(1234);
+        mock_router(&vm, ROLL, U256::from(5), Some(seed));
+
+        let nonce = request(&mut consumer, ROLL, seed).unwrap();
+        assert_eq!(consumer.vrf.client_seed(nonce), seed);
+
+        vm.set_sender(ROUTER);
+        consumer.on_roll(nonce, vec![U256::ONE]).unwrap();
+        assert_eq!(consumer.vrf.client_seed(nonce), U256::ZERO);
+    }
+
+    #[test]
+    fn fulfill_rejects_unauthorized_callers() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        consumer.open(U256::ONE, ROLL);
+
+        vm.set_sender(REQUESTER);
+        let err = consumer.on_roll(U256::ONE, vec![U256::ONE]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnauthorizedFulfiller(SupraVrfUnauthorizedFulfiller { fulfiller })
+                if fulfiller == REQUESTER
+        ));
+        assert!(consumer.vrf.is_pending(U256::ONE));
+
+        consumer.vrf._set_fulfiller(REQUESTER, true);
+        consumer.on_roll(U256::ONE, vec![U256::ONE]).unwrap();
+        assert!(!consumer.vrf.is_pending(U256::ONE));
+    }
+
+    #[test]
+    fn pending_requests_fulfill_out_of_order() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        let nonces = [U256::from(1), U256::from(2), U256::from(3)];
+        for nonce in nonces {
+            consumer.open(nonce, ROLL);
+        }
+
+        vm.set_sender(ROUTER);
+        for (delivered, nonce) in [nonces[2], nonces[0], nonces[1]].into_iter().enumerate() {
+            let word = U256::from(10 + delivered);
+            consumer.on_roll(nonce, vec![word]).unwrap();
+            assert_eq!(consumer.last_word.get(), word);
+            assert!(!consumer.vrf.is_pending(nonce));
+        }
+        assert!(nonces.iter().all(|&nonce| !consumer.vrf.is_pending(nonce)));
+
+        // A duplicate delivery finds nothing to fulfill.
+        let err = consumer.on_roll(nonces[0], vec![U256::ONE]).unwrap_err();
+        assert!(matches!(err, Error::UnknownRequest(_)));
+    }
+
+    #[test]
+    fn fulfill_rejects_kind_mismatch() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        consumer.open(U256::ONE, DRAW);
+
+        vm.set_sender(ROUTER);
+        let err = consumer.on_roll(U256::ONE, vec![U256::ONE]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::KindMismatch(SupraVrfKindMismatch { expected, actual, .. })
+                if expected == ROLL && actual == DRAW
+        ));
+        assert!(consumer.vrf.is_pending(U256::ONE));
+
+        consumer.on_draw(U256::ONE, vec![U256::ONE]).unwrap();
+        assert!(!consumer.vrf.is_pending(U256::ONE));
+    }
+
+    #[test]
+    fn close_moves_requests_to_terminal_states() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        consumer.open(U256::ONE, ROLL);
+
+        let err = consumer
+            .vrf
+            ._close(U256::ONE, RequestStatus::Requested)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidTransition(_)));
+
+        let closed = consumer
+            .vrf
+            ._close(U256::ONE, RequestStatus::Cancelled)
+            .unwrap();
+        assert_eq!(closed.requester, REQUESTER);
+        assert_eq!(closed.status, RequestStatus::Requested);
+        assert_eq!(consumer.vrf.request(U256::ONE).status, RequestStatus::None);
+
+        let err = consumer
+            .vrf
+            ._close(U256::ONE, RequestStatus::Expired)
+            .unwrap_err();
+        assert!(matches!(err, Error::UnknownRequest(_)));
+
+        vm.set_sender(ROUTER);
+        let err = consumer.on_roll(U256::ONE, vec![U256::ONE]).unwrap_err();
+        assert!(matches!(err, Error::UnknownRequest(_)));
+    }
+
+    #[test]
+    #[cfg(feature = "dev-vrf")]
+    fn dev_fulfill_delivers_in_process() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        vm.set_sender(REQUESTER);
+
+        let nonce = request(&mut consumer, DRAW, U256::ZERO).unwrap();
+        assert!(consumer.vrf.is_pending(nonce));
+        dev_fulfill(
+            &mut consumer,
+            |s| &mut s.vrf,
+            <TestConsumer as Router<TestConsumer>>::route,
+            nonce,
+            1,
+        )
+        .unwrap();
+
+        assert!(!consumer.vrf.is_pending(nonce));
+        assert!(!consumer.last_word.get().is_zero());
+        assert!(!consumer.vrf.is_fulfiller(REQUESTER));
+
+        let err = dev_fulfill(
+            &mut consumer,
+            |s| &mut s.vrf,
+            <TestConsumer as Router<TestConsumer>>::route,
+            nonce,
+            1,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::UnknownRequest(_)));
+    }
+
+    #[test]
+    fn registered_callbacks_select_exported_functions() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        vm.set_sender(ROUTER);
+
+        for (kind, signature) in CALLBACKS {
+            let registered = consumer.vrf.callback_signature(kind);
+            assert_eq!(registered, signature);
+
+            let nonce = U256::from(kind);
+            let word = U256::from(100 + kind);
+            consumer.open(nonce, kind);
+            let result = consumer.deliver(&registered, nonce, word);
+            assert!(
+                matches!(result, Some(Ok(_))),
+                "`{registered}` does not select a callback"
+            );
+            assert!(!consumer.vrf.is_pending(nonce));
+            assert_eq!(consumer.last_word.get(), word);
+        }
+    }
+
+    #[test]
+    fn misnamed_callback_selects_nothing() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        vm.set_sender(ROUTER);
+
+        consumer.open(U256::ONE, ROLL);
+        let result = consumer.deliver("onRandomness(uint256,uint256[])", U256::ONE, U256::ONE);
+        assert!(result.is_none());
+        assert!(consumer.vrf.is_pending(U256::ONE));
+    }
+
+    #[test]
+    fn consumer_interface_reflects_state() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        let fulfiller = address!("00000000000000000000000000000000000000f2");
+        consumer.vrf._set_fulfiller(fulfiller, true);
+        consumer.open(U256::ONE, ROLL);
+
+        assert_eq!(consumer.supra_router(), ROUTER);
+        assert_eq!(consumer.supra_client_wallet(), CLIENT_WALLET);
+        assert!(consumer.is_request_pending(U256::ONE));
+        assert!(!consumer.is_request_pending(U256::from(2)));
+        assert!(consumer.is_supra_fulfiller(ROUTER));
+        assert!(consumer.is_supra_fulfiller(fulfiller));
+        assert!(!consumer.is_supra_fulfiller(REQUESTER));
+    }
+
+    #[test]
+    fn consumer_interface_is_exported() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+        consumer.open(U256::ONE, ROLL);
+
+        let selector = u32::from_be_bytes(keccak(b"supraRouter()")[..4].try_into().unwrap());
+        let output = <TestConsumer as Router<TestConsumer>>::route(&mut consumer, selector, &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(output, ROUTER.abi_encode());
+
+        let selector = u32::from_be_bytes(
+            keccak(b"isRequestPending(uint256)")[..4]
+                .try_into()
+                .unwrap(),
+        );
+        let output = <TestConsumer as Router<TestConsumer>>::route(
+            &mut consumer,
+            selector,
+            &U256::ONE.abi_encode(),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(output, true.abi_encode());
+    }
+
+    const DEPOSIT: Address = address!("00000000000000000000000000000000000000d1");
+    const FEE: U256 = U256::from_limbs([100, 0, 0, 0]);
+
+    /// Deploys a consumer registered at Supra as its own client, with `FEE`
+    /// forwarded to `DEPOSIT` per request and the router mocked.
+    #[cfg(not(feature = "dev-vrf"))]
+    fn deploy_with_fee(vm: &TestVM) -> TestConsumer {
+        let mut consumer = TestConsumer::deploy(vm);
+        consumer.vrf.client_wallet.set(vm.contract_address());
+        vm.set_code(DEPOSIT, vec![0xfe]);
+        consumer.vrf._set_request_fee(DEPOSIT, FEE).unwrap();
+
+        let (_, signature) = CALLBACKS[0];
+        let mut calldata = keccak(b"generateRequest(string,uint8,uint256,address)")[..4].to_vec();
+        calldata.extend(
+            (
+                alloc::string::String::from(signature),
+                1u8,
+                U256::ONE,
+                vm.contract_address(),
+            )
+                .abi_encode_params(),
+        );
+        vm.mock_call(ROUTER, calldata, Ok(U256::from(5).abi_encode()));
+        consumer
+    }
+
+    /// Makes the mocked deposit contract accept or reject `depositFundClient`.
+    #[cfg(not(feature = "dev-vrf"))]
+    fn mock_deposit(vm: &TestVM, accept: bool) {
+        let calldata = keccak(b"depositFundClient()")[..4].to_vec();
+        let result = if accept {
+            Ok(Vec::new())
+        } else {
+            Err(Vec::new())
+        };
+        vm.mock_call(DEPOSIT, calldata, result);
+    }
+
+    #[test]
+    fn fee_setup_is_validated() {
+        let vm = TestVM::default();
+        let mut consumer = TestConsumer::deploy(&vm);
+
+        // A zero fee disables charging, whatever the deposit.
+        consumer
+            .vrf
+            ._set_request_fee(Address::ZERO, U256::ZERO)
+            .unwrap();
+
+        let err = consumer
+            .vrf
+            ._set_request_fee(Address::ZERO, FEE)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidFeeDeposit(_)));
+        let err = consumer.vrf._set_request_fee(DEPOSIT, FEE).unwrap_err();
+        assert!(matches!(err, Error::InvalidFeeDeposit(_)));
+
+        vm.set_code(DEPOSIT, vec![0xfe]);
+        let err = consumer.vrf._set_request_fee(DEPOSIT, FEE).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::FeeClientMismatch(SupraVrfFeeClientMismatch { clientWallet })
+                if clientWallet == CLIENT_WALLET
+        ));
+
+        consumer.vrf.client_wallet.set(vm.contract_address());
+        consumer.vrf._set_request_fee(DEPOSIT, FEE).unwrap();
+        assert_eq!(consumer.vrf.request_fee(), FEE);
+        assert_eq!(consumer.vrf.fee_deposit(), DEPOSIT);
+        let logs = vm.get_emitted_logs();
+        let (topics, _) = logs.last().unwrap();
+        assert_eq!(topics[0], RequestFeeUpdated::SIGNATURE_HASH);
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-vrf"))]
+    fn request_rejects_insufficient_fee() {
+        let vm = TestVM::default();
+        let mut consumer = deploy_with_fee(&vm);
+        mock_deposit(&vm, true);
+        vm.set_sender(REQUESTER);
+        vm.set_value(FEE);
+
+        let paid = FEE - U256::ONE;
+        let err = consumer
+            .vrf
+            ._request_randomness(ROLL, 1, U256::ONE, REQUESTER, 0, U256::ZERO, paid)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InsufficientFee(SupraVrfInsufficientFee { paid: p, fee })
+                if p == paid && fee == FEE
+        ));
+        assert!(!consumer.vrf.is_pending(U256::from(5)));
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-vrf"))]
+    fn request_rejects_budget_above_value() {
+        let vm = TestVM::default();
+        let mut consumer = deploy_with_fee(&vm);
+        mock_deposit(&vm, true);
+        vm.set_sender(REQUESTER);
+        vm.set_value(FEE);
+
+        let paid = FEE + U256::ONE;
+        let err = consumer
+            .vrf
+            ._request_randomness(ROLL, 1, U256::ONE, REQUESTER, 0, U256::ZERO, paid)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::FeeExceedsValue(SupraVrfFeeExceedsValue { paid: p, value })
+                if p == paid && value == FEE
+        ));
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-vrf"))]
+    fn request_forwards_fee_to_deposit() {
+        let vm = TestVM::default();
+        let mut consumer = deploy_with_fee(&vm);
+        vm.set_sender(REQUESTER);
+        vm.set_value(FEE);
+
+        mock_deposit(&vm, false);
+        let err = consumer
+            .vrf
+            ._request_randomness(ROLL, 1, U256::ONE, REQUESTER, 0, U256::ZERO, FEE)
+            .unwrap_err();
+        assert!(matches!(err, Error::FeeTransferFailed(_)));
+
+        mock_deposit(&vm, true);
+        let nonce = consumer
+            .vrf
+            ._request_randomness(ROLL, 1, U256::ONE, REQUESTER, 0, U256::ZERO, FEE)
+            .unwrap();
+        assert!(consumer.vrf.is_pending(nonce));
+    }
+
+    #[test]
+    #[cfg(not(feature = "dev-vrf"))]
+    fn request_refunds_excess_to_caller() {
+        let vm = TestVM::default();
+        let mut consumer = deploy_with_fee(&vm);
+        mock_deposit(&vm, true);
+        vm.set_sender(REQUESTER);
+        vm.set_value(FEE * U256::from(3));
+        vm.set_balance(vm.contract_address(), FEE * U256::from(3));
+
+        let paid = FEE * U256::from(3);
+        consumer
+            .vrf
+            ._request_randomness(ROLL, 1, U256::ONE, REQUESTER, 0, U256::ZERO, paid)
+            .unwrap();
+        assert_eq!(vm.balance(REQUESTER), FEE * U256::from(2));
+    }
+}
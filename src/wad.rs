@@ -0,0 +1,246 @@
+//! Fixed-point arithmetic on 18-decimal ("WAD") numbers.
+//!
+//! A WAD `x` represents the real number `x / 1e18`. Every operation is
+//! bounds-checked and returns `None` instead of overflowing or dividing by
+//! zero, so callers decide how to surface the failure.
+use stylus_sdk::alloy_primitives::{uint, U256};
+
+/// `1.0` as a WAD.
+pub const WAD: U256 = uint!(1_000_000_000_000_000_000_U256);
+
+/// `ln(2)` as a WAD.
+const LN2_WAD: U256 = uint!(693_147_180_559_945_309_U256);
+
+/// Largest input for which [`exp_wad`] fits in a `U256`, `ln(2^255 / 1e18)`.
+pub const EXP_WAD_MAX: U256 = uint!(135_305_999_368_893_231_588_U256);
+
+/// Inputs from which [`exp_neg_wad`] rounds down to zero, `ln(1e18)`.
+const EXP_NEG_WAD_MIN_ZERO: U256 = uint!(41_446_531_673_892_822_312_U256);
+
+/// Number of Taylor terms used by [`exp_wad`] on the reduced input.
+const EXP_TERMS: u64 = 24;
+
+/// Returns `x * y`, rounded down.
+pub fn mul_wad(x: U256, y: U256) -> Option<U256> {
+    x.checked_mul(y).map(|product| product / WAD)
+}
+
+/// Returns `x * y`, rounded up.
+pub fn mul_wad_up(x: U256, y: U256) -> Option<U256> {
+    x.checked_mul(y).map(|product| div_up(product, WAD))
+}
+
+/// Returns `x / y`, rounded down.
+pub fn div_wad(x: U256, y: U256) -> Option<U256> {
+    if y.is_zero() {
+        return None;
+    }
+    x.checked_mul(WAD).map(|scaled| scaled / y)
+}
+
+/// Returns `x / y`, rounded up.
+pub fn div_wad_up(x: U256, y: U256) -> Option<U256> {
+    if y.is_zero() {
+        return None;
+    }
+    x.checked_mul(WAD).map(|scaled| div_up(scaled, y))
+}
+
+/// Returns `x^n` for an integer exponent `n`, rounding down at every step.
+pub fn pow_wad(x: U256, n: u32) -> Option<U256> {
+    let mut result = WAD;
+    let mut base = x;
+    let mut exponent = n;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_wad(result, base)?;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = mul_wad(base, base)?;
+        }
+    }
+    Some(result)
+}
+
+/// Returns an approximation of `e^x`.
+///
+/// The input is reduced to `x = k * ln(2) + r` with `0 <= r < ln(2)`, `e^r`
+/// is evaluated with a Taylor series and the result is scaled by `2^k`.
+/// `ln(2)` is truncated to WAD precision, which costs up to one part in
+/// `1e18` per factor of two, so the relative error grows with `x`: a few
+/// parts in `1e18` for small inputs and within `2e-16` up to
+/// [`EXP_WAD_MAX`].
+///
+/// Returns `None` if `x` exceeds [`EXP_WAD_MAX`].
+pub fn exp_wad(x: U256) -> Option<U256> {
+    if x > EXP_WAD_MAX {
+        return None;
+    }
+
+    let k = x / LN2_WAD;
+    let r = x - k * LN2_WAD;
+
+    let mut term = WAD;
+    let mut sum = WAD;
+    for i in 1..=EXP_TERMS {
+        term = term * r / (WAD * U256::from(i));
+        if term.is_zero() {
+            break;
+        }
+        sum += term;
+    }
+    Some(sum << k.to::<usize>())
+}
+
+/// Returns an approximation of `e^-x`, rounding down to zero once the
+/// result drops below one wei.
+pub fn exp_neg_wad(x: U256) -> U256 {
+    if x >= EXP_NEG_WAD_MIN_ZERO {
+        return U256::ZERO;
+    }
+    // `x < ln(1e18) < EXP_WAD_MAX`, so the exponential exists and is at
+    // least `WAD`.
+    let exp = exp_wad(x).expect("input should be below `EXP_WAD_MAX`");
+    WAD * WAD / exp
+}
+
+fn div_up(x: U256, y: U256) -> U256 {
+    let quotient = x / y;
+    if (x % y).is_zero() {
+        quotient
+    } else {
+        quotient + U256::ONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stylus_sdk::alloy_primitives::{uint, U256};
+
+    use super::*;
+
+    /// `e` as a WAD.
+    const E_WAD: U256 = uint!(2_718_281_828_459_045_235_U256);
+
+    /// Asserts `actual` is within `max_diff` of `expected`.
+    fn assert_close(actual: U256, expected: U256, max_diff: U256) {
+        let diff = if actual > expected {
+            actual - expected
+        } else {
+            expected - actual
+        };
+        assert!(
+            diff <= max_diff,
+            "{actual} differs from {expected} by {diff}"
+        );
+    }
+
+    #[test]
+    fn mul_and_div_round_as_named() {
+        // 1.5 * 1e-18 = 1.5e-18
+        let x = uint!(1_500_000_000_000_000_000_U256);
+        assert_eq!(mul_wad(x, U256::ONE), U256::ONE);
+        assert_eq!(mul_wad_up(x, U256::ONE), U256::from(2));
+        assert_eq!(mul_wad_up(WAD, WAD), WAD);
+
+        // 1 / 3
+        let three = U256::from(3) * WAD;
+        assert_eq!(div_wad(WAD, three), uint!(333_333_333_333_333_333_U256));
+        assert_eq!(div_wad_up(WAD, three), uint!(333_333_333_333_333_334_U256));
+        assert_eq!(div_wad_up(three, three), WAD);
+    }
+
+    #[test]
+    fn mul_and_div_report_overflow_and_zero_division() {
+        assert_eq!(mul_wad(U256::MAX, U256::from(2)), None);
+        assert_eq!(mul_wad_up(U256::MAX, U256::from(2)), None);
+        assert_eq!(div_wad(U256::MAX, WAD), None);
+        assert_eq!(div_wad_up(U256::MAX, WAD), None);
+        assert_eq!(div_wad(WAD, U256::ZERO), None);
+        assert_eq!(div_wad_up(WAD, U256::ZERO), None);
+    }
+
+    #[test]
+    fn pow_wad_reference_values() {
+        let two = U256::from(2) * WAD;
+        assert_eq!(pow_wad(two, 0), Some(WAD));
+        assert_eq!(pow_wad(two, 1), Some(two));
+        assert_eq!(pow_wad(two, 10), Some(U256::from(1024) * WAD));
+        assert_eq!(pow_wad(U256::ZERO, 3), Some(U256::ZERO));
+
+        // 0.5^3 = 0.125 exactly, 0.9^2 = 0.81 exactly.
+        let half = WAD / U256::from(2);
+        assert_eq!(pow_wad(half, 3), Some(WAD / U256::from(8)));
+        let nine_tenths = uint!(900_000_000_000_000_000_U256);
+        assert_eq!(
+            pow_wad(nine_tenths, 2),
+            Some(uint!(810_000_000_000_000_000_U256))
+        );
+
+        // Rounds down: (1e-18)^2 = 1e-36.
+        assert_eq!(pow_wad(U256::ONE, 2), Some(U256::ZERO));
+    }
+
+    #[test]
+    fn pow_wad_reports_overflow() {
+        let big = U256::from(1u128 << 100) * WAD;
+        assert_eq!(pow_wad(big, 3), None);
+    }
+
+    #[test]
+    fn exp_wad_reference_values() {
+        assert_eq!(exp_wad(U256::ZERO), Some(WAD));
+        assert_close(exp_wad(WAD).unwrap(), E_WAD, U256::from(20));
+        // e^ln(2) = 2.
+        assert_close(
+            exp_wad(LN2_WAD).unwrap(),
+            U256::from(2) * WAD,
+            U256::from(10),
+        );
+        // e^10 = 22026.465794806716516957...
+        assert_close(
+            exp_wad(U256::from(10) * WAD).unwrap(),
+            uint!(22_026_465_794_806_716_516_957_U256),
+            uint!(100_000_U256),
+        );
+    }
+
+    #[test]
+    fn exp_wad_upper_bound() {
+        // `e^EXP_WAD_MAX` is `2^255` in WAD terms. A wrapped shift would land
+        // far from it, so closeness shows the result did not overflow. The
+        // tolerance is the documented `2e-16` relative error.
+        let max = exp_wad(EXP_WAD_MAX).unwrap();
+        let expected = U256::ONE << 255;
+        assert_close(
+            max,
+            expected,
+            expected / U256::from(5_000_000_000_000_000u64),
+        );
+        assert_eq!(exp_wad(EXP_WAD_MAX + U256::ONE), None);
+        assert_eq!(exp_wad(U256::MAX), None);
+    }
+
+    #[test]
+    fn exp_neg_wad_reference_values() {
+        assert_eq!(exp_neg_wad(U256::ZERO), WAD);
+        // e^-1 = 0.367879441171442321...
+        assert_close(
+            exp_neg_wad(WAD),
+            uint!(367_879_441_171_442_321_U256),
+            U256::from(10),
+        );
+    }
+
+    #[test]
+    fn exp_neg_wad_cutoff() {
+        assert_eq!(exp_neg_wad(EXP_NEG_WAD_MIN_ZERO), U256::ZERO);
+        assert_eq!(exp_neg_wad(U256::MAX), U256::ZERO);
+        // Just below the cutoff, e^-x is about one wei.
+        let below = exp_neg_wad(EXP_NEG_WAD_MIN_ZERO - U256::ONE);
+        assert!(below <= U256::ONE);
+        // Well below it the result is still positive.
+        assert!(!exp_neg_wad(EXP_NEG_WAD_MIN_ZERO - WAD).is_zero());
+    }
+}
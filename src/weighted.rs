@@ -0,0 +1,279 @@
+//! Weighted random selection over a mutable set of members.
+//!
+//! [`WeightedSet`] keeps a Fenwick (binary indexed) tree of member weights in
+//! storage, so inserting, updating and removing a member as well as sampling
+//! one proportionally to its weight all cost `O(log n)` storage accesses.
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+    storage::{StorageAddress, StorageMap, StorageU256, StorageVec},
+};
+
+use crate::rand_utils;
+
+/// State of a weighted selection set.
+#[storage]
+pub struct WeightedSet {
+    /// Fenwick tree over slot weights. Node `i` (0-based) covers the slots
+    /// `(i + 1 - lowbit(i + 1))..=i`.
+    pub(crate) tree: StorageVec<StorageU256>,
+    /// Raw weight of each slot.
+    pub(crate) weights: StorageVec<StorageU256>,
+    /// Member occupying each slot, `Address::ZERO` if free.
+    pub(crate) members: StorageVec<StorageAddress>,
+    /// Slot of each member plus one, zero if absent.
+    pub(crate) slot_of: StorageMap<Address, StorageU256>,
+    /// Slots released by [`WeightedSet::remove`], reused on insertion.
+    pub(crate) free_slots: StorageVec<StorageU256>,
+}
+
+impl WeightedSet {
+    /// Returns whether `member` is in the set.
+    pub fn contains(&self, member: Address) -> bool {
+        self.slot(member).is_some()
+    }
+
+    /// Returns the weight of `member`, zero if absent.
+    pub fn weight_of(&self, member: Address) -> U256 {
+        self.slot(member)
+            .and_then(|slot| self.weights.get(slot))
+            .unwrap_or_default()
+    }
+
+    /// Returns the sum of all weights.
+    pub fn total_weight(&self) -> U256 {
+        self.prefix_sum(self.tree.len())
+    }
+
+    /// Adds `member` with `weight`. Returns `false` if it is already present
+    /// or is `Address::ZERO`, which marks free slots.
+    pub fn insert(&mut self, member: Address, weight: U256) -> bool {
+        if member.is_zero() || self.contains(member) {
+            return false;
+        }
+
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                let slot = slot.to::<usize>();
+                self.adjust(slot, U256::ZERO, weight);
+                self.set_slot(slot, member, weight);
+                slot
+            }
+            None => {
+                let slot = self.tree.len();
+                let index = slot + 1;
+                let node = weight + self.prefix_sum(slot) - self.prefix_sum(index - lowbit(index));
+                self.tree.push(node);
+                self.weights.push(weight);
+                self.members.push(member);
+                slot
+            }
+        };
+        self.slot_of.setter(member).set(U256::from(slot + 1));
+        true
+    }
+
+    /// Sets the weight of `member`. Returns `false` if it is not present.
+    pub fn update(&mut self, member: Address, weight: U256) -> bool {
+        let Some(slot) = self.slot(member) else {
+            return false;
+        };
+        let old = self.weights.get(slot).unwrap_or_default();
+        self.adjust(slot, old, weight);
+        self.set_slot(slot, member, weight);
+        true
+    }
+
+    /// Removes `member`. Returns `false` if it is not present.
+    pub fn remove(&mut self, member: Address) -> bool {
+        let Some(slot) = self.slot(member) else {
+            return false;
+        };
+        let old = self.weights.get(slot).unwrap_or_default();
+        self.adjust(slot, old, U256::ZERO);
+        self.set_slot(slot, Address::ZERO, U256::ZERO);
+        self.slot_of.delete(member);
+        self.free_slots.push(U256::from(slot));
+        true
+    }
+
+    /// Picks a member with probability proportional to its weight, using
+    /// `word` as the source of randomness. Returns `None` if the total
+    /// weight is zero.
+    pub fn sample(&self, word: U256) -> Option<Address> {
+        let total = self.total_weight();
+        if total.is_zero() {
+            return None;
+        }
+        let mut remaining = rand_utils::uniform(word, U256::ZERO, total - U256::ONE);
+
+        // Descend the tree to the first slot whose prefix sum exceeds
+        // `remaining`.
+        let len = self.tree.len();
+        let mut pos = 0;
+        let mut step = 1 << (usize::BITS - 1 - len.leading_zeros());
+        while step > 0 {
+            if pos + step <= len {
+                let node = self.tree.get(pos + step - 1).unwrap_or_default();
+                if node <= remaining {
+                    pos += step;
+                    remaining -= node;
+                }
+            }
+            step >>= 1;
+        }
+        self.members.get(pos)
+    }
+
+    fn slot(&self, member: Address) -> Option<usize> {
+        let slot = self.slot_of.get(member);
+        (!slot.is_zero()).then(|| slot.to::<usize>() - 1)
+    }
+
+    fn set_slot(&mut self, slot: usize, member: Address, weight: U256) {
+        if let Some(mut entry) = self.weights.setter(slot) {
+            entry.set(weight);
+        }
+        if let Some(mut entry) = self.members.setter(slot) {
+            entry.set(member);
+        }
+    }
+
+    /// Moves the weight of `slot` from `old` to `new` in the tree.
+    fn adjust(&mut self, slot: usize, old: U256, new: U256) {
+        let len = self.tree.len();
+        let mut index = slot + 1;
+        while index <= len {
+            if let Some(mut node) = self.tree.setter(index - 1) {
+                let value = node.get();
+                node.set(if new >= old {
+                    value + (new - old)
+                } else {
+                    value - (old - new)
+                });
+            }
+            index += lowbit(index);
+        }
+    }
+
+    /// Returns the sum of the weights of the first `count` slots.
+    fn prefix_sum(&self, count: usize) -> U256 {
+        let mut sum = U256::ZERO;
+        let mut index = count;
+        while index > 0 {
+            sum += self.tree.get(index - 1).unwrap_or_default();
+            index -= lowbit(index);
+        }
+        sum
+    }
+}
+
+fn lowbit(index: usize) -> usize {
+    index & index.wrapping_neg()
+}
+
+#[cfg(test)]
+mod tests {
+    use stylus_sdk::{
+        alloy_primitives::{address, Address, U256},
+        testing::*,
+    };
+
+    use super::WeightedSet;
+    use crate::rand_utils;
+
+    const ALICE: Address = address!("a11ceacf9aa32246d767fccd72e02d6bcbcc375d");
+    const BOB: Address = address!("b0b0cb49ec2e96df5f5ffb081acae66a2cbbc2e2");
+    const CAROL: Address = address!("ca201f1bbcc6ae3a8e2d55d2d6a7d9e1f3a5b7c9");
+
+    fn word(i: usize) -> U256 {
+        rand_utils::derive(U256::from(i), U256::ZERO)
+    }
+
+    #[test]
+    fn insert_update_remove() {
+        let vm = TestVM::default();
+        let mut set = WeightedSet::from(&vm);
+
+        assert!(set.insert(ALICE, U256::from(3)));
+        assert!(set.insert(BOB, U256::from(5)));
+        assert!(!set.insert(ALICE, U256::from(7)));
+        assert_eq!(set.weight_of(ALICE), U256::from(3));
+        assert_eq!(set.total_weight(), U256::from(8));
+
+        assert!(set.update(ALICE, U256::from(1)));
+        assert!(!set.update(CAROL, U256::from(1)));
+        assert_eq!(set.total_weight(), U256::from(6));
+
+        assert!(set.remove(BOB));
+        assert!(!set.remove(BOB));
+        assert!(!set.contains(BOB));
+        assert_eq!(set.weight_of(BOB), U256::ZERO);
+        assert_eq!(set.total_weight(), U256::from(1));
+    }
+
+    #[test]
+    fn insert_rejects_zero_address() {
+        let vm = TestVM::default();
+        let mut set = WeightedSet::from(&vm);
+
+        assert!(!set.insert(Address::ZERO, U256::from(10)));
+        assert!(!set.contains(Address::ZERO));
+        assert_eq!(set.total_weight(), U256::ZERO);
+        assert_eq!(set.sample(word(0)), None);
+    }
+
+    #[test]
+    fn removed_slots_are_reused() {
+        let vm = TestVM::default();
+        let mut set = WeightedSet::from(&vm);
+
+        set.insert(ALICE, U256::from(2));
+        set.insert(BOB, U256::from(4));
+        set.remove(ALICE);
+        set.insert(CAROL, U256::from(6));
+
+        assert_eq!(set.members.len(), 2);
+        assert_eq!(set.members.get(0), Some(CAROL));
+        assert_eq!(set.total_weight(), U256::from(10));
+        assert_eq!(set.weight_of(CAROL), U256::from(6));
+    }
+
+    #[test]
+    fn sample_is_proportional_to_weight() {
+        let vm = TestVM::default();
+        let mut set = WeightedSet::from(&vm);
+
+        set.insert(ALICE, U256::from(1));
+        set.insert(BOB, U256::ZERO);
+        set.insert(CAROL, U256::from(3));
+
+        let trials = 4_000;
+        let (mut alice, mut carol) = (0usize, 0usize);
+        for i in 0..trials {
+            match set.sample(word(i)) {
+                Some(member) if member == ALICE => alice += 1,
+                Some(member) if member == CAROL => carol += 1,
+                other => panic!("unexpected sample {other:?}"),
+            }
+        }
+        assert!(alice.abs_diff(trials / 4) <= trials / 40);
+        assert!(carol.abs_diff(trials * 3 / 4) <= trials / 40);
+    }
+
+    #[test]
+    fn sample_skips_removed_members() {
+        let vm = TestVM::default();
+        let mut set = WeightedSet::from(&vm);
+
+        set.insert(ALICE, U256::from(5));
+        set.insert(BOB, U256::from(5));
+        set.remove(ALICE);
+        for i in 0..200 {
+            assert_eq!(set.sample(word(i)), Some(BOB));
+        }
+
+        set.remove(BOB);
+        assert_eq!(set.sample(word(0)), None);
+    }
+}